@@ -0,0 +1,257 @@
+use futures::{StreamExt, stream};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use tokio_util::sync::CancellationToken;
+
+use crate::http::client::{ApiClient, encode_path, ensure_not_cancelled};
+use crate::models::{DomainError, EXECUTABLE_MODE, FileEntry, REGULAR_MODE, RefKind, SYMLINK_MODE};
+use crate::source::TreeListing;
+
+const SRC_PAGE_LEN: u32 = 100;
+// Обход дерева по каталогам: ограничение на число каталогов и параллельность запросов
+const MAX_TREE_DIRECTORIES: usize = 2_000;
+const TREE_WALK_CONCURRENCY: usize = 8;
+
+/// Страница списка Bitbucket: адрес следующей приходит в теле, а не в заголовке `Link`.
+#[derive(Debug, Deserialize)]
+pub struct Page<T> {
+    pub values: Vec<T>,
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MainBranch {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RepoMetadata {
+    /// У пустого репозитория основной ветки нет
+    pub mainbranch: Option<MainBranch>,
+    #[serde(default)]
+    pub has_issues: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct RefTarget {
+    hash: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RefResponse {
+    target: RefTarget,
+}
+
+#[derive(Debug, Deserialize)]
+struct SrcEntry {
+    #[serde(rename = "type")]
+    kind: String,
+    path: String,
+    #[serde(default)]
+    size: u64,
+    /// `link`, `executable`, `binary`, `lfs`
+    #[serde(default)]
+    attributes: Vec<String>,
+}
+
+impl SrcEntry {
+    // Режим git в `/src/` не отдаётся, восстанавливаем его по атрибутам
+    fn mode(&self) -> &'static str {
+        if self.attributes.iter().any(|a| a == "link") {
+            SYMLINK_MODE
+        } else if self.attributes.iter().any(|a| a == "executable") {
+            EXECUTABLE_MODE
+        } else {
+            REGULAR_MODE
+        }
+    }
+}
+
+/// Клиент Bitbucket Cloud API 2.0. `owner` — workspace.
+#[derive(Clone)]
+pub struct BitbucketClient {
+    api: ApiClient,
+}
+
+impl BitbucketClient {
+    pub fn new(api: ApiClient) -> Self {
+        Self { api }
+    }
+
+    pub fn api(&self) -> &ApiClient {
+        &self.api
+    }
+
+    pub fn repo_url(&self, workspace: &str, repo: &str, path: &str) -> String {
+        self.api
+            .api_url(&format!("repositories/{workspace}/{repo}{path}"))
+    }
+
+    pub async fn get_page<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        context: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Page<T>, DomainError> {
+        self.api.get_json(url, context, cancel).await
+    }
+
+    pub async fn get_repo_metadata(
+        &self,
+        workspace: &str,
+        repo: &str,
+        cancel: &CancellationToken,
+    ) -> Result<RepoMetadata, DomainError> {
+        let url = self.repo_url(workspace, repo, "");
+        self.api
+            .get_json(&url, "Bitbucket Repositories API", cancel)
+            .await
+    }
+
+    /// Коммит, на который указывает ветка, тег или (в том числе сокращённый) SHA;
+    /// `None` — такого ref нет.
+    pub async fn ref_target(
+        &self,
+        workspace: &str,
+        repo: &str,
+        kind: RefKind,
+        name: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        let name = encode_path(name);
+        let result = match kind {
+            RefKind::Branch | RefKind::Tag => {
+                let collection = if kind == RefKind::Branch {
+                    "branches"
+                } else {
+                    "tags"
+                };
+                let url = self.repo_url(workspace, repo, &format!("/refs/{collection}/{name}"));
+                self.api
+                    .get_json::<RefResponse>(&url, "Bitbucket Refs API", cancel)
+                    .await
+                    .map(|r| r.target.hash)
+            }
+            RefKind::Commit => {
+                let url = self.repo_url(workspace, repo, &format!("/commit/{name}"));
+                self.api
+                    .get_json::<RefTarget>(&url, "Bitbucket Commits API", cancel)
+                    .await
+                    .map(|c| c.hash)
+            }
+        };
+
+        match result {
+            Ok(hash) => Ok(Some(hash)),
+            Err(DomainError::NotFound) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Полный список файлов коммита. `/src/` перечисляет один каталог постранично,
+    /// поэтому дерево обходится по уровням, каталоги уровня — параллельно.
+    pub async fn get_tree(
+        &self,
+        workspace: &str,
+        repo: &str,
+        commit: &str,
+        cancel: &CancellationToken,
+    ) -> Result<TreeListing, DomainError> {
+        let mut files = Vec::new();
+        let mut truncated = false;
+        let mut visited = 0usize;
+        let mut level = vec![String::new()];
+
+        while !level.is_empty() {
+            if visited + level.len() > MAX_TREE_DIRECTORIES {
+                level.truncate(MAX_TREE_DIRECTORIES - visited);
+                truncated = true;
+            }
+            visited += level.len();
+
+            let listings: Vec<Result<Vec<SrcEntry>, DomainError>> =
+                stream::iter(level.drain(..).map(|dir| async move {
+                    self.list_directory(workspace, repo, commit, &dir, cancel)
+                        .await
+                }))
+                .buffer_unordered(TREE_WALK_CONCURRENCY)
+                .collect()
+                .await;
+
+            let mut next_level = Vec::new();
+            for entries in listings {
+                for entry in entries? {
+                    match entry.kind.as_str() {
+                        "commit_file" => files.push(FileEntry {
+                            mode: Some(entry.mode().to_string()),
+                            path: entry.path,
+                            size: entry.size,
+                            sha: None,
+                        }),
+                        "commit_directory" => next_level.push(entry.path),
+                        _ => {}
+                    }
+                }
+            }
+            level = next_level;
+        }
+
+        if files.is_empty() {
+            return Err(DomainError::NotFound);
+        }
+
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        // Подмодули в ответе `/src/` не разбираются
+        Ok(TreeListing {
+            files,
+            truncated,
+            submodules: Vec::new(),
+            unreadable: 0,
+        })
+    }
+
+    async fn list_directory(
+        &self,
+        workspace: &str,
+        repo: &str,
+        commit: &str,
+        dir: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<SrcEntry>, DomainError> {
+        let mut entries = Vec::new();
+        let mut next = Some(self.repo_url(
+            workspace,
+            repo,
+            &format!("/src/{commit}/{}?pagelen={SRC_PAGE_LEN}", encode_path(dir)),
+        ));
+        while let Some(url) = next {
+            ensure_not_cancelled(cancel)?;
+            let page: Page<SrcEntry> = self.get_page(&url, "Bitbucket Source API", cancel).await?;
+            entries.extend(page.values);
+            next = page.next;
+        }
+        Ok(entries)
+    }
+
+    /// Для пути файла `/src/` отдаёт его содержимое как есть.
+    pub async fn get_raw_content(
+        &self,
+        workspace: &str,
+        repo: &str,
+        commit: &str,
+        path: &str,
+        cancel: &CancellationToken,
+    ) -> Result<String, DomainError> {
+        ensure_not_cancelled(cancel)?;
+
+        let url = self.repo_url(
+            workspace,
+            repo,
+            &format!("/src/{commit}/{}", encode_path(path)),
+        );
+        self.api
+            .get_text(self.api.get(&url), "Bitbucket Source API", cancel)
+            .await
+    }
+}
@@ -0,0 +1,135 @@
+use serde::Deserialize;
+use tokio_util::sync::CancellationToken;
+
+use crate::bitbucket::client::{BitbucketClient, Page};
+use crate::models::{DomainError, Issue};
+
+#[derive(Debug, Deserialize)]
+struct Account {
+    nickname: Option<String>,
+    display_name: Option<String>,
+}
+
+impl Account {
+    fn name(&self) -> Option<String> {
+        self.nickname.clone().or_else(|| self.display_name.clone())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Named {
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Content {
+    raw: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HtmlLink {
+    href: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IssueLinks {
+    html: Option<HtmlLink>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IssueResponse {
+    id: u64,
+    title: String,
+    state: String,
+    kind: Option<String>,
+    priority: Option<String>,
+    reporter: Option<Account>,
+    assignee: Option<Account>,
+    created_on: Option<String>,
+    updated_on: Option<String>,
+    component: Option<Named>,
+    milestone: Option<Named>,
+    content: Option<Content>,
+    links: Option<IssueLinks>,
+}
+
+/// Issues репозитория; если трекер в репозитории выключен, возвращает `IssuesDisabled`.
+pub async fn fetch_issues(
+    client: &BitbucketClient,
+    workspace: &str,
+    repo: &str,
+    cancel: &CancellationToken,
+) -> Result<(Vec<Issue>, bool), DomainError> {
+    const PAGE_LEN: u32 = 50;
+    const MAX_PAGES: u32 = 20; // до 1000 issues
+    let mut all = Vec::new();
+    let mut next = Some(client.repo_url(
+        workspace,
+        repo,
+        &format!("/issues?pagelen={PAGE_LEN}&sort=-updated_on"),
+    ));
+
+    for _ in 0..MAX_PAGES {
+        let Some(url) = next.take() else {
+            break;
+        };
+        if cancel.is_cancelled() {
+            return Err(DomainError::Cancelled);
+        }
+
+        let page: Page<IssueResponse> =
+            match client.get_page(&url, "Bitbucket Issues API", cancel).await {
+                // без трекера Bitbucket отвечает 404 так же, как на чужой репозиторий
+                Err(DomainError::NotFound) => {
+                    let metadata = client.get_repo_metadata(workspace, repo, cancel).await?;
+                    return Err(if metadata.has_issues {
+                        DomainError::NotFound
+                    } else {
+                        DomainError::IssuesDisabled
+                    });
+                }
+                result => result?,
+            };
+
+        all.extend(page.values.iter().map(to_issue));
+        next = page.next;
+    }
+
+    Ok((all, next.is_some()))
+}
+
+fn to_issue(item: &IssueResponse) -> Issue {
+    // Собственных меток у Bitbucket нет — их роль играют тип, приоритет и компонент
+    let labels = [
+        item.kind.clone(),
+        item.priority.clone(),
+        item.component.as_ref().and_then(|c| c.name.clone()),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    // new, open и on hold — открытые; resolved, closed, invalid, duplicate, wontfix — закрытые
+    let open = matches!(item.state.as_str(), "new" | "open" | "on hold");
+
+    Issue {
+        number: item.id,
+        title: item.title.clone(),
+        state: if open { "open" } else { "closed" }.to_string(),
+        author: item.reporter.as_ref().and_then(Account::name),
+        created_at: item.created_on.clone(),
+        updated_at: item.updated_on.clone(),
+        // дату закрытия и число комментариев список issues не отдаёт
+        closed_at: None,
+        comments: None,
+        labels,
+        milestone: item.milestone.as_ref().and_then(|m| m.name.clone()),
+        assignees: item.assignee.iter().filter_map(Account::name).collect(),
+        body: item.content.as_ref().and_then(|c| c.raw.clone()),
+        html_url: item
+            .links
+            .as_ref()
+            .and_then(|l| l.html.as_ref())
+            .and_then(|h| h.href.clone()),
+    }
+}
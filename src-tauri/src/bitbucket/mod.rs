@@ -0,0 +1,4 @@
+pub mod client;
+pub mod issues;
+pub mod parser;
+pub mod source;
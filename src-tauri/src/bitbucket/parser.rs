@@ -0,0 +1,84 @@
+use url::Url;
+
+use crate::github::parser::{invalid, parse_line_range, remote_to_web_url, split_ref_suffix};
+use crate::models::{DomainError, Forge, LineRange, RefKind, RepoInfo};
+
+/// Адрес репозитория Bitbucket Cloud: веб-URL (`https://bitbucket.org/ws/repo/src/main/docs`),
+/// SSH-адрес или `bitbucket.org/ws/repo`. Заполняет `RepoInfo` так же, как разбор URL GitHub.
+pub fn parse_bitbucket_input(input: &str, web_host: &str) -> Result<RepoInfo, DomainError> {
+    let normalized = input.trim();
+    let candidate = match remote_to_web_url(normalized, web_host)? {
+        Some(url) => url,
+        None if normalized.starts_with("http://") || normalized.starts_with("https://") => {
+            normalized.to_string()
+        }
+        None => format!("https://{normalized}"),
+    };
+
+    let url =
+        Url::parse(&candidate).map_err(|_| invalid(normalized, "the URL could not be parsed"))?;
+    let segments: Vec<String> = url
+        .path_segments()
+        .map(|s| s.filter(|s| !s.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default();
+
+    let [workspace, name, rest @ ..] = segments.as_slice() else {
+        return Err(invalid(
+            normalized,
+            "the URL must include the workspace and repository, e.g. /workspace/repo",
+        ));
+    };
+
+    let (repo, mut branch) = split_ref_suffix(name);
+    let repo = repo.trim_end_matches(".git").to_string();
+
+    let mut subdirectory: Option<String> = None;
+    let mut ref_kind: Option<RefKind> = None;
+    let mut ref_path: Option<String> = None;
+    let mut single_file = false;
+
+    match rest {
+        [view, reference, path @ ..] if view == "src" || view == "raw" => {
+            single_file = !path.is_empty();
+            branch = Some(reference.clone());
+            if !path.is_empty() {
+                subdirectory = Some(path.join("/"));
+                ref_path = Some(rest[1..].join("/"));
+            }
+        }
+        [view, sha, ..] if view == "commits" => {
+            branch = Some(sha.clone());
+            ref_kind = Some(RefKind::Commit);
+        }
+        [view, name @ ..] if view == "branch" && !name.is_empty() => {
+            branch = Some(name.join("/"));
+            ref_kind = Some(RefKind::Branch);
+        }
+        _ => {}
+    }
+
+    Ok(RepoInfo {
+        owner: workspace.clone(),
+        repo,
+        branch,
+        subdirectory,
+        original_url: normalized.to_string(),
+        commit_sha: None,
+        ref_kind,
+        ref_path,
+        single_file,
+        line_range: url
+            .fragment()
+            .filter(|_| single_file)
+            .and_then(parse_lines_fragment),
+        forge: Forge::Bitbucket,
+        host: Some(web_host.trim_end_matches('/').to_string()),
+    })
+}
+
+/// `lines-40` или `lines-40:120` — так Bitbucket отмечает выделенные строки.
+fn parse_lines_fragment(fragment: &str) -> Option<LineRange> {
+    let lines = fragment.strip_prefix("lines-")?;
+    let (start, end) = lines.split_once(':').unwrap_or((lines, lines));
+    parse_line_range(&format!("L{start}-L{end}"))
+}
@@ -0,0 +1,74 @@
+use tokio_util::sync::CancellationToken;
+
+use crate::bitbucket::client::BitbucketClient;
+use crate::bitbucket::issues::fetch_issues;
+use crate::models::{DomainError, Issue, RefKind, RepoInfo};
+use crate::refs::{RefLookup, resolve_repository_files};
+use crate::source::{IssueSource, Source, TreeListing, content_ref};
+
+// Архив Bitbucket отдаёт только веб-хост, а не API, поэтому файлы читаются по одному
+impl Source for BitbucketClient {
+    async fn list_files(
+        &self,
+        input: &RepoInfo,
+        cancel: &CancellationToken,
+    ) -> Result<(RepoInfo, TreeListing), DomainError> {
+        resolve_repository_files(self, input, cancel).await
+    }
+
+    async fn read_file(
+        &self,
+        repo: &RepoInfo,
+        path: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        self.get_raw_content(&repo.owner, &repo.repo, content_ref(repo), path, cancel)
+            .await
+            .map(Some)
+    }
+}
+
+impl IssueSource for BitbucketClient {
+    async fn fetch_issues(
+        &self,
+        repo: &RepoInfo,
+        cancel: &CancellationToken,
+    ) -> Result<(Vec<Issue>, bool), DomainError> {
+        fetch_issues(self, &repo.owner, &repo.repo, cancel).await
+    }
+}
+
+// Эндпоинты веток и тегов отдают хеш коммита вместе с ответом, так что ref разрешается
+// в коммит одним запросом
+impl RefLookup for BitbucketClient {
+    async fn ref_target(
+        &self,
+        workspace: &str,
+        repo: &str,
+        kind: RefKind,
+        name: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        BitbucketClient::ref_target(self, workspace, repo, kind, name, cancel).await
+    }
+
+    async fn default_branch(
+        &self,
+        workspace: &str,
+        repo: &str,
+        cancel: &CancellationToken,
+    ) -> Result<String, DomainError> {
+        let metadata = self.get_repo_metadata(workspace, repo, cancel).await?;
+        Ok(metadata.mainbranch.ok_or(DomainError::NoFiles)?.name)
+    }
+
+    async fn get_tree(
+        &self,
+        workspace: &str,
+        repo: &str,
+        sha: &str,
+        cancel: &CancellationToken,
+    ) -> Result<TreeListing, DomainError> {
+        BitbucketClient::get_tree(self, workspace, repo, sha, cancel).await
+    }
+}
@@ -0,0 +1,269 @@
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::SystemTime;
+
+use git2::{ObjectType, Oid};
+use tempfile::NamedTempFile;
+
+use crate::local::run_blocking;
+use crate::models::DomainError;
+
+pub const DEFAULT_MAX_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Содержимое blob-объектов на диске по их SHA: один и тот же blob одинаков в любом
+/// репозитории и на любом коммите, поэтому неизменённые файлы повторно не скачиваются.
+/// Кэш — только оптимизация: ошибки диска не срывают экспорт, а считаются промахом.
+#[derive(Clone)]
+pub struct BlobCache {
+    dir: PathBuf,
+    budget: SizeBudget,
+}
+
+impl BlobCache {
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        Self {
+            dir,
+            budget: SizeBudget::new(max_bytes),
+        }
+    }
+
+    /// Сколько из перечисленных blob-объектов уже лежит в кэше; содержимое не читается.
+    pub async fn count_cached(&self, shas: Vec<String>) -> usize {
+        let dir = self.dir.clone();
+        run_blocking(move || {
+            Ok(shas
+                .iter()
+                .filter_map(|sha| blob_path(&dir, sha))
+                .filter(|path| path.is_file())
+                .count())
+        })
+        .await
+        .unwrap_or(0)
+    }
+
+    /// Blob из кэша; время изменения файла обновляется — по нему вытесняем.
+    pub async fn get(&self, sha: &str) -> Option<String> {
+        let path = blob_path(&self.dir, sha)?;
+        run_blocking(move || {
+            let content = fs::read_to_string(&path).ok();
+            if content.is_some() {
+                touch(&path);
+            }
+            Ok(content)
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
+    /// Сохраняет скачанный blob, если его ещё нет, и вытесняет давно не использованные сверх лимита.
+    /// Кэш общий для всех хостов, поэтому содержимое, не совпадающее с SHA, не сохраняется:
+    /// иначе ответ одного сервера подменил бы файл в экспорте другого.
+    pub async fn put(&self, sha: &str, content: &str) {
+        let Some(path) = blob_path(&self.dir, sha) else {
+            return;
+        };
+        let cache = self.clone();
+        let sha = sha.to_string();
+        let content = content.to_string();
+        let _ = run_blocking(move || {
+            if path.is_file() {
+                touch(&path);
+                return Ok(());
+            }
+            if !is_blob_sha(&sha, &content) {
+                return Ok(());
+            }
+            write_atomically(&path, &content)?;
+            cache.budget.record(&cache.dir, content.len() as u64)
+        })
+        .await;
+    }
+}
+
+/// Занятый кэшем объём без обхода каталога на каждую запись: каталог обходится при первой
+/// записи, дальше сумма растёт на записанные байты, а вытеснение (снова обход) запускается,
+/// только когда она превысила лимит. Перезапись файла учитывается как новый файл — оценка
+/// лишь завышается, и следующее вытеснение её уточнит.
+#[derive(Clone)]
+pub struct SizeBudget {
+    max_bytes: u64,
+    used: Arc<Mutex<Option<u64>>>,
+}
+
+impl SizeBudget {
+    pub fn new(max_bytes: u64) -> Self {
+        Self {
+            max_bytes,
+            used: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Учитывает `added` только что записанных в `dir` байт.
+    pub fn record(&self, dir: &Path, added: u64) -> Result<(), DomainError> {
+        let mut used = self.used.lock().unwrap_or_else(PoisonError::into_inner);
+        let total = match *used {
+            Some(used) => used.saturating_add(added),
+            None => disk_usage(dir)?,
+        };
+        *used = Some(if total > self.max_bytes {
+            evict(dir, self.max_bytes)?
+        } else {
+            total
+        });
+        Ok(())
+    }
+}
+
+/// Удаляет давно не использованные (по времени изменения) файлы кэша `ab/<ключ>`,
+/// пока их суммарный размер больше `max_bytes`; возвращает оставшийся объём.
+fn evict(dir: &Path, max_bytes: u64) -> Result<u64, DomainError> {
+    let mut entries = Vec::new();
+    for shard in fs::read_dir(dir)? {
+        for entry in fs::read_dir(shard?.path())? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            entries.push((modified, metadata.len(), entry.path()));
+        }
+    }
+
+    let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+    entries.sort_by_key(|(modified, _, _)| *modified);
+    for (_, size, path) in entries {
+        if total <= max_bytes {
+            break;
+        }
+        if fs::remove_file(path).is_ok() {
+            total -= size;
+        }
+    }
+    Ok(total)
+}
+
+// Суммарный размер файлов `ab/<ключ>`; несуществующий каталог — пустой кэш
+fn disk_usage(dir: &Path) -> Result<u64, DomainError> {
+    match fs::read_dir(dir) {
+        Ok(shards) => Ok(shards
+            .flatten()
+            .flat_map(|shard| fs::read_dir(shard.path()).into_iter().flatten().flatten())
+            .filter_map(|entry| entry.metadata().ok())
+            .map(|metadata| metadata.len())
+            .sum()),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(0),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Удаляет весь кэш; возвращает освобождённый объём в байтах.
+pub fn clear(dir: &Path) -> Result<u64, DomainError> {
+    let freed = disk_usage(dir)?;
+    match fs::remove_dir_all(dir) {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(freed),
+    }
+}
+
+// `ab/abcdef...`; SHA проверяется, чтобы строка из ответа API не стала путём вне кэша
+pub fn blob_path(dir: &Path, sha: &str) -> Option<PathBuf> {
+    if sha.len() < 4 || !sha.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let sha = sha.to_ascii_lowercase();
+    Some(dir.join(&sha[..2]).join(&sha))
+}
+
+// SHA-1 git-объекта `blob <длина>\0<содержимое>`; SHA-256-репозитории просто не кэшируются
+fn is_blob_sha(sha: &str, content: &str) -> bool {
+    Oid::hash_object(ObjectType::Blob, content.as_bytes())
+        .is_ok_and(|oid| oid.to_string().eq_ignore_ascii_case(sha))
+}
+
+// Через временный файл: прерванная запись не оставит в кэше обрезанный blob
+pub fn write_atomically(path: &Path, content: &str) -> Result<(), DomainError> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| DomainError::Io("cache path has no parent".into()))?;
+    fs::create_dir_all(parent)?;
+    let mut temp = NamedTempFile::new_in(parent)?;
+    temp.write_all(content.as_bytes())?;
+    temp.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+// Время изменения — время последнего использования
+pub fn touch(path: &Path) {
+    let _ = File::options()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_modified(SystemTime::now()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sha(content: &str) -> String {
+        Oid::hash_object(ObjectType::Blob, content.as_bytes())
+            .unwrap()
+            .to_string()
+    }
+
+    fn set_age(dir: &Path, sha: &str, secs_ago: u64) {
+        let path = blob_path(dir, sha).unwrap();
+        let time = SystemTime::now() - std::time::Duration::from_secs(secs_ago);
+        File::options()
+            .write(true)
+            .open(path)
+            .and_then(|file| file.set_modified(time))
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn stores_and_returns_blobs_by_sha() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = BlobCache::new(dir.path().to_path_buf(), DEFAULT_MAX_BYTES);
+        let content = "fn main() {}\n";
+
+        assert_eq!(cache.get(&sha(content)).await, None);
+        cache.put(&sha(content), content).await;
+        assert_eq!(cache.get(&sha(content)).await.as_deref(), Some(content));
+        assert_eq!(
+            cache.count_cached(vec![sha(content), sha("other")]).await,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_content_that_does_not_match_its_sha() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = BlobCache::new(dir.path().to_path_buf(), DEFAULT_MAX_BYTES);
+        let genuine = sha("genuine\n");
+
+        cache.put(&genuine, "forged\n").await;
+        assert_eq!(cache.get(&genuine).await, None);
+        cache.put("../../etc", "x").await;
+        assert_eq!(disk_usage(dir.path()).unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn evicts_least_recently_used_blobs_over_the_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = BlobCache::new(dir.path().to_path_buf(), 25);
+        let [first, second, third] = ["first 10\n", "second 10", "third 10\n"];
+
+        cache.put(&sha(first), first).await;
+        cache.put(&sha(second), second).await;
+        set_age(dir.path(), &sha(first), 60);
+        set_age(dir.path(), &sha(second), 30);
+        cache.put(&sha(third), third).await;
+
+        assert_eq!(cache.get(&sha(first)).await, None);
+        assert_eq!(cache.get(&sha(second)).await.as_deref(), Some(second));
+        assert_eq!(cache.get(&sha(third)).await.as_deref(), Some(third));
+        assert_eq!(clear(dir.path()).unwrap(), 18);
+        assert_eq!(cache.get(&sha(third)).await, None);
+    }
+}
@@ -1,18 +1,29 @@
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::time::Duration;
 
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 use tokio_util::sync::CancellationToken;
 
+use crate::blob_cache::{self, BlobCache, DEFAULT_MAX_BYTES};
 use crate::converter::issues::export_issues_to_markdown;
-use crate::converter::processor::convert_repository_to_markdown;
-use crate::github::client::GitHubClient;
-use crate::github::parser::parse_repository_input;
+use crate::converter::processor::{self, convert_to_markdown};
+use crate::forge::{ForgeClient, find_forge_host, host_profile, parse_forge_input};
+use crate::github::parser::same_host;
+use crate::http::cache::ResponseCache;
+use crate::http::client::{ApiClient, ClientConfig};
+use crate::http::retry::RetryPolicy;
+use crate::local::run_blocking;
 use crate::models::{
-    ConversionResult, ConvertOptions, DomainError, IssuesExportOptions, IssuesExportResult,
-    RepoInput,
+    AppSettings, ConnectionOptions, ConversionResult, ConvertOptions, DomainError, Forge,
+    HostProfile, IssuesExportOptions, IssuesExportResult, LocalInput, RepoInfo, RepoInput,
 };
+use crate::settings;
+
+const BLOB_CACHE_DIR: &str = "blobs";
+const RESPONSE_CACHE_DIR: &str = "responses";
 
 #[derive(Default)]
 pub struct ConversionState {
@@ -38,6 +49,113 @@ fn map_error(err: DomainError) -> String {
     err.to_string()
 }
 
+fn load_app_settings(app: &AppHandle) -> AppSettings {
+    app.path()
+        .app_config_dir()
+        .map(|dir| settings::load_settings(&dir))
+        .unwrap_or_default()
+}
+
+fn retry_policy(connection: &ConnectionOptions) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: connection.max_attempts.max(1),
+        request_timeout: Duration::from_secs(connection.request_timeout_secs.max(1)),
+        ..RetryPolicy::default()
+    }
+}
+
+fn github_profile(settings: &AppSettings, connection: &ConnectionOptions) -> HostProfile {
+    connection
+        .host
+        .clone()
+        .or_else(|| settings.github_host.clone())
+        .unwrap_or_default()
+}
+
+// Хост и токен выбираются по хосту из ввода. Токен и хост GitHub из опций запроса
+// приоритетнее сохранённых, но уходят только на сам настроенный хост GitHub: github.com
+// при настроенном GHES и другие хостинги получают токен из своей записи в `forge_hosts`
+fn client_config(
+    settings: &AppSettings,
+    connection: &ConnectionOptions,
+    repo: &RepoInfo,
+) -> ClientConfig {
+    let github = github_profile(settings, connection);
+    let web_host = repo.host.as_deref().unwrap_or(&github.web_host);
+    if repo.forge != Forge::GitHub || !same_host(web_host, &github.web_host) {
+        let configured = find_forge_host(&settings.forge_hosts, web_host);
+        return ClientConfig {
+            token: configured.and_then(|h| h.token.clone()),
+            host: host_profile(repo.forge, web_host, configured),
+            wait_on_rate_limit: connection.wait_on_rate_limit,
+            retry: retry_policy(connection),
+        };
+    }
+
+    let token = connection
+        .token
+        .clone()
+        .filter(|t| !t.trim().is_empty())
+        .or_else(|| settings.github_token.clone());
+    ClientConfig {
+        token,
+        host: github,
+        wait_on_rate_limit: connection.wait_on_rate_limit,
+        retry: retry_policy(connection),
+    }
+}
+
+/// Разбирает ввод и создаёт клиент хостинга, которому принадлежит репозиторий.
+fn connect(
+    app: &AppHandle,
+    repo_input: &str,
+    connection: &ConnectionOptions,
+) -> Result<(ForgeClient, RepoInfo), String> {
+    let settings = load_app_settings(app);
+    let repo = parse_forge_input(
+        repo_input,
+        &github_profile(&settings, connection).web_host,
+        &settings.forge_hosts,
+    )
+    .map_err(map_error)?;
+
+    let mut api = ApiClient::new(client_config(&settings, connection, &repo)).map_err(map_error)?;
+    if let (Some(max_bytes), Ok(dir)) = (
+        cache_max_bytes(&settings),
+        cache_dir(app, RESPONSE_CACHE_DIR),
+    ) {
+        api = api.with_response_cache(ResponseCache::new(dir, max_bytes));
+    }
+    let app = app.clone();
+    let api = api.with_rate_limit_listener(move |status| {
+        let _ = app.emit("rate-limit", status);
+    });
+    Ok((ForgeClient::new(repo.forge, api), repo))
+}
+
+fn cache_dir(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    app.path()
+        .app_cache_dir()
+        .map(|dir| dir.join(name))
+        .map_err(|e| e.to_string())
+}
+
+/// Лимит каждого из кэшей на диске (blob-объекты, ответы API); `None` — кэши выключены.
+fn cache_max_bytes(settings: &AppSettings) -> Option<u64> {
+    match settings.blob_cache_max_mb {
+        Some(0) => None,
+        Some(mb) => Some(mb.saturating_mul(1024 * 1024)),
+        None => Some(DEFAULT_MAX_BYTES),
+    }
+}
+
+/// Кэш blob-объектов; `None`, если он выключен в настройках или каталог кэша неизвестен.
+fn blob_cache(app: &AppHandle) -> Option<BlobCache> {
+    let max_bytes = cache_max_bytes(&load_app_settings(app))?;
+    let dir = cache_dir(app, BLOB_CACHE_DIR).ok()?;
+    Some(BlobCache::new(dir, max_bytes))
+}
+
 #[tauri::command]
 pub async fn convert_repo_to_markdown(
     app: AppHandle,
@@ -46,7 +164,8 @@ pub async fn convert_repo_to_markdown(
     options: ConvertOptions,
 ) -> Result<ConversionResult, String> {
     let cancel = state.set_new();
-    let client = GitHubClient::new().map_err(map_error)?;
+    let (client, repo_info) = connect(&app, &input.repo, &options.connection)?;
+    let cache = blob_cache(&app);
 
     let progress_emitter = |current: u64, total: u64| {
         let _ = app.emit(
@@ -58,9 +177,41 @@ pub async fn convert_repo_to_markdown(
         );
     };
 
-    convert_repository_to_markdown(
+    convert_to_markdown(
         &client,
-        &input.repo,
+        &repo_info,
+        &options,
+        cache.as_ref(),
+        input.output_path.as_deref(),
+        &cancel,
+        progress_emitter,
+    )
+    .await
+    .map_err(map_error)
+}
+
+#[tauri::command]
+pub async fn convert_local_to_markdown(
+    app: AppHandle,
+    state: State<'_, ConversionState>,
+    input: LocalInput,
+    options: ConvertOptions,
+) -> Result<ConversionResult, String> {
+    let cancel = state.set_new();
+
+    let progress_emitter = |current: u64, total: u64| {
+        let _ = app.emit(
+            "conversion-progress",
+            serde_json::json!({
+              "current": current,
+              "total": total,
+            }),
+        );
+    };
+
+    processor::convert_local_to_markdown(
+        Path::new(&input.path),
+        input.reference.as_deref(),
         &options,
         input.output_path.as_deref(),
         &cancel,
@@ -78,8 +229,7 @@ pub async fn export_issues(
     options: IssuesExportOptions,
 ) -> Result<IssuesExportResult, String> {
     let cancel = state.set_new();
-    let client = GitHubClient::new().map_err(map_error)?;
-    let repo_info = parse_repository_input(&input.repo).map_err(map_error)?;
+    let (client, repo_info) = connect(&app, &input.repo, &options.connection)?;
 
     let progress_emitter = |current: u64, total: u64| {
         let _ = app.emit(
@@ -106,6 +256,30 @@ pub async fn cancel_conversion(state: State<'_, ConversionState>) -> Result<(),
     Ok(())
 }
 
+#[tauri::command]
+pub async fn get_settings(app: AppHandle) -> Result<AppSettings, String> {
+    Ok(settings::mask_tokens(load_app_settings(&app)))
+}
+
+#[tauri::command]
+pub async fn save_settings(app: AppHandle, settings: AppSettings) -> Result<(), String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    let settings = settings::restore_tokens(settings, &settings::load_settings(&dir));
+    settings::save_settings(&dir, &settings).map_err(map_error)
+}
+
+/// Удаляет кэш скачанных файлов и ответов API; возвращает освобождённый объём в байтах.
+#[tauri::command]
+pub async fn clear_cache(app: AppHandle) -> Result<u64, String> {
+    let dirs = [
+        cache_dir(&app, BLOB_CACHE_DIR)?,
+        cache_dir(&app, RESPONSE_CACHE_DIR)?,
+    ];
+    run_blocking(move || dirs.iter().map(|dir| blob_cache::clear(dir)).sum())
+        .await
+        .map_err(map_error)
+}
+
 #[tauri::command]
 pub async fn read_file_chunk(
     path: String,
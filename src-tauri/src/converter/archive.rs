@@ -0,0 +1,212 @@
+use std::io::{Read, Seek};
+
+use flate2::read::GzDecoder;
+use tar::Archive;
+use tokio_util::sync::CancellationToken;
+use zip::ZipArchive;
+
+use crate::http::client::ensure_not_cancelled;
+use crate::models::{DomainError, FileEntry};
+
+fn zip_error(err: zip::result::ZipError) -> DomainError {
+    DomainError::Io(err.to_string())
+}
+
+// Архивы GitHub (и большинство ручных выгрузок) кладут всё в один каталог `owner-repo-sha/`
+fn strip_top_level(path: &str) -> Option<&str> {
+    path.split_once('/')
+        .map(|(_, rest)| rest)
+        .filter(|rest| !rest.is_empty())
+}
+
+/// Лежат ли все пути внутри одного общего каталога верхнего уровня.
+pub fn has_common_top_level<'a>(paths: impl IntoIterator<Item = &'a str>) -> bool {
+    let mut top: Option<&str> = None;
+    for path in paths {
+        let Some((first, _)) = path.split_once('/') else {
+            return false;
+        };
+        match top {
+            Some(top) if top != first => return false,
+            _ => top = Some(first),
+        }
+    }
+    top.is_some()
+}
+
+// Путь записи в экспорте; `None` — запись вне общего каталога или сам каталог
+fn entry_path(raw_path: &str, strip_top: bool) -> Option<&str> {
+    if strip_top {
+        strip_top_level(raw_path)
+    } else {
+        Some(raw_path).filter(|p| !p.is_empty())
+    }
+}
+
+/// Потоково читает tar.gz и отдаёт файлы в `on_file` по одному, не накапливая их
+/// (с `strip_top` — без общего верхнего каталога). `keep` решает по пути и размеру,
+/// нужен ли файл: тела остальных записей не читаются. Ошибка `on_file` прерывает чтение.
+pub fn read_tar_gz(
+    reader: impl Read,
+    cancel: &CancellationToken,
+    strip_top: bool,
+    mut keep: impl FnMut(&FileEntry) -> bool,
+    mut on_file: impl FnMut(FileEntry, String) -> Result<(), DomainError>,
+) -> Result<(), DomainError> {
+    let mut archive = Archive::new(GzDecoder::new(reader));
+
+    for entry in archive.entries()? {
+        ensure_not_cancelled(cancel)?;
+
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let raw_path = entry.path()?.to_string_lossy().into_owned();
+        let Some(path) = entry_path(&raw_path, strip_top) else {
+            continue;
+        };
+
+        let file = FileEntry {
+            path: path.to_string(),
+            size: entry.size(),
+            mode: None,
+            sha: None,
+        };
+        if !keep(&file) {
+            continue;
+        }
+
+        let mut buffer = Vec::with_capacity(file.size as usize);
+        entry.read_to_end(&mut buffer)?;
+        on_file(file, String::from_utf8_lossy(&buffer).into_owned())?;
+    }
+
+    Ok(())
+}
+
+/// То же для zip: записи с путями вне архива (`../`) пропускаются.
+pub fn read_zip(
+    reader: impl Read + Seek,
+    cancel: &CancellationToken,
+    strip_top: bool,
+    mut keep: impl FnMut(&FileEntry) -> bool,
+    mut on_file: impl FnMut(FileEntry, String) -> Result<(), DomainError>,
+) -> Result<(), DomainError> {
+    let mut archive = ZipArchive::new(reader).map_err(zip_error)?;
+
+    for index in 0..archive.len() {
+        ensure_not_cancelled(cancel)?;
+
+        let mut entry = archive.by_index(index).map_err(zip_error)?;
+        if !entry.is_file() {
+            continue;
+        }
+        let Some(raw_path) = entry.enclosed_name() else {
+            continue;
+        };
+        let raw_path = raw_path.to_string_lossy().replace('\\', "/");
+        let Some(path) = entry_path(&raw_path, strip_top) else {
+            continue;
+        };
+
+        let file = FileEntry {
+            path: path.to_string(),
+            size: entry.size(),
+            mode: None,
+            sha: None,
+        };
+        if !keep(&file) {
+            continue;
+        }
+
+        let mut buffer = Vec::with_capacity(file.size as usize);
+        entry.read_to_end(&mut buffer)?;
+        on_file(file, String::from_utf8_lossy(&buffer).into_owned())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::{Cursor, Write};
+
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    use super::*;
+
+    /// tar.gz из пар (путь, содержимое) в указанном порядке.
+    pub(crate) fn tar_gz(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::fast()));
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    pub(crate) fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, content) in files {
+            writer
+                .start_file(*path, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn read_all(
+        read: impl FnOnce(
+            &mut dyn FnMut(FileEntry, String) -> Result<(), DomainError>,
+        ) -> Result<(), DomainError>,
+    ) -> Vec<(String, String)> {
+        let mut files = Vec::new();
+        read(&mut |file, content| {
+            files.push((file.path, content));
+            Ok(())
+        })
+        .unwrap();
+        files
+    }
+
+    #[test]
+    fn reads_kept_entries_without_the_top_level_directory() {
+        let files = [
+            ("repo-abc/README.md", "readme\n"),
+            ("repo-abc/src/main.rs", "fn main() {}\n"),
+            ("repo-abc/Cargo.lock", "# lock\n"),
+        ];
+        let cancel = CancellationToken::new();
+        let keep = |file: &FileEntry| !file.path.ends_with(".lock");
+        let expected = [
+            ("README.md".to_string(), "readme\n".to_string()),
+            ("src/main.rs".to_string(), "fn main() {}\n".to_string()),
+        ];
+
+        let from_tar =
+            read_all(|on_file| read_tar_gz(&tar_gz(&files)[..], &cancel, true, keep, on_file));
+        let from_zip =
+            read_all(|on_file| read_zip(Cursor::new(zip(&files)), &cancel, true, keep, on_file));
+        assert_eq!(from_tar, expected);
+        assert_eq!(from_zip, expected);
+    }
+
+    #[test]
+    fn detects_a_common_top_level_directory() {
+        assert!(has_common_top_level(["repo/a.rs", "repo/src/b.rs"]));
+        assert!(!has_common_top_level(["repo/a.rs", "other/b.rs"]));
+        assert!(!has_common_top_level(["repo/a.rs", "README.md"]));
+        assert!(!has_common_top_level([]));
+    }
+}
@@ -1,6 +1,6 @@
 use globset::{Glob, GlobSet, GlobSetBuilder};
 
-use crate::models::{ConvertOptions, FileEntry};
+use crate::models::{ConvertOptions, FileEntry, Submodule};
 
 const LARGE_FILE_THRESHOLD: u64 = 1_048_576; // 1MB
 
@@ -41,8 +41,18 @@ impl SkipMatcher {
     }
 }
 
+/// Size is also checked against content already read: not every forge (GitLab)
+/// reports it in the file listing.
+pub fn is_large_file(size: u64) -> bool {
+    size > LARGE_FILE_THRESHOLD
+}
+
+pub fn exceeds_size_limit(size: u64, options: &ConvertOptions) -> bool {
+    options.skip_large_files && is_large_file(size)
+}
+
 pub fn should_skip_file(file: &FileEntry, matcher: &SkipMatcher, options: &ConvertOptions) -> bool {
-    if options.skip_large_files && file.size > LARGE_FILE_THRESHOLD {
+    if exceeds_size_limit(file.size, options) {
         return true;
     }
 
@@ -58,6 +68,42 @@ pub fn should_skip_file(file: &FileEntry, matcher: &SkipMatcher, options: &Conve
     matcher.is_match(&file.path)
 }
 
+pub fn in_scope(path: &str, scope: &str) -> bool {
+    path == scope
+        || path
+            .strip_prefix(scope)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Keeps files inside the given directories (or matching a file path).
+/// An empty list of scopes means the whole repository.
+pub fn scope_files(files: Vec<FileEntry>, scopes: &[String]) -> Vec<FileEntry> {
+    if scopes.is_empty() {
+        return files;
+    }
+
+    files
+        .into_iter()
+        .filter(|f| scopes.iter().any(|scope| in_scope(&f.path, scope)))
+        .collect()
+}
+
+/// Submodules inside the export scopes, and those that contain a scope themselves.
+pub fn scope_submodules(submodules: Vec<Submodule>, scopes: &[String]) -> Vec<Submodule> {
+    if scopes.is_empty() {
+        return submodules;
+    }
+
+    submodules
+        .into_iter()
+        .filter(|s| {
+            scopes
+                .iter()
+                .any(|scope| in_scope(&s.path, scope) || in_scope(scope, &s.path))
+        })
+        .collect()
+}
+
 pub fn filter_files<'a>(
     files: &'a [FileEntry],
     matcher: &SkipMatcher,
@@ -7,11 +7,10 @@ use time::OffsetDateTime;
 use time::format_description::well_known::Rfc3339;
 use tokio_util::sync::CancellationToken;
 
-use crate::github::client::GitHubClient;
-use crate::github::issues::fetch_issues;
 use crate::models::{
     DomainError, Issue, IssuesExportOptions, IssuesExportResult, IssuesStats, RepoInfo,
 };
+use crate::source::IssueSource;
 
 pub fn filter_issues<'a>(issues: &'a [Issue], opts: &IssuesExportOptions) -> Vec<&'a Issue> {
     issues
@@ -88,13 +87,14 @@ fn build_issues_header(
     included_states: &str,
 ) -> String {
     format!(
-        "# Issues Export for {}/{}\n\n- **Generated:** {generated}\n- **Total Issues:** {}\n- **Open Issues:** {}\n- **Closed Issues:** {}\n- **Included States:** {}\n\nThis document consolidates GitHub issues into a single Markdown file for review, backups, or AI ingestion.\n",
+        "# Issues Export for {}/{}\n\n- **Generated:** {generated}\n- **Total Issues:** {}\n- **Open Issues:** {}\n- **Closed Issues:** {}\n- **Included States:** {}\n\nThis document consolidates {} issues into a single Markdown file for review, backups, or AI ingestion.\n",
         repo.owner,
         repo.repo,
         issues.len(),
         open_count,
         closed_count,
-        included_states
+        included_states,
+        repo.forge.display_name()
     )
 }
 
@@ -142,16 +142,15 @@ fn generate_issues_markdown(
     content
 }
 
-pub async fn export_issues_to_markdown(
-    client: &GitHubClient,
+pub async fn export_issues_to_markdown<S: IssueSource>(
+    source: &S,
     repo_info: &RepoInfo,
     options: &IssuesExportOptions,
     output_path: Option<&str>,
     cancel: &CancellationToken,
     mut on_progress: impl FnMut(u64, u64),
 ) -> Result<IssuesExportResult, DomainError> {
-    let (all_issues, truncated) =
-        fetch_issues(client, &repo_info.owner, &repo_info.repo, cancel).await?;
+    let (all_issues, truncated) = source.fetch_issues(repo_info, cancel).await?;
 
     let filtered = filter_issues(&all_issues, options);
     let total = filtered.len() as u64;
@@ -193,7 +192,10 @@ pub async fn export_issues_to_markdown(
             );
             let filename = format!(
                 "{}-{}-{}-issues.md",
-                repo_info.owner, repo_info.repo, ts_file
+                // у вложенных групп GitLab владелец содержит '/'
+                repo_info.owner.replace('/', "-"),
+                repo_info.repo,
+                ts_file
             );
             std::env::temp_dir().join(filename)
         }
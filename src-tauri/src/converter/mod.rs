@@ -1,4 +1,6 @@
+pub mod archive;
 pub mod files;
 pub mod issues;
 pub mod processor;
+pub mod submodules;
 pub mod tree;
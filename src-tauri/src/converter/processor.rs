@@ -1,17 +1,31 @@
+use std::collections::HashMap;
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use futures::stream::BoxStream;
+use futures::{StreamExt, stream};
 use tempfile::{NamedTempFile, PersistError};
 use time::OffsetDateTime;
 use time::format_description::well_known::Rfc3339;
 use tokio_util::sync::CancellationToken;
 
-use crate::converter::files::{SkipMatcher, filter_files};
+use crate::blob_cache::BlobCache;
+use crate::converter::files::{
+    SkipMatcher, exceeds_size_limit, filter_files, in_scope, is_large_file, scope_files,
+    scope_submodules,
+};
+use crate::converter::submodules::{attach_urls, submodule_lines};
 use crate::converter::tree::generate_directory_tree;
-use crate::github::client::GitHubClient;
-use crate::github::parser::parse_repository_input;
-use crate::models::{ConversionResult, ConvertOptions, DomainError, FileEntry, RepoInfo, Stats};
+use crate::lfs::{LfsPointer, parse_pointer};
+use crate::local::archive::{is_archive, load_archive_snapshot};
+use crate::local::directory::LocalDirectory;
+use crate::local::git::load_git_snapshot;
+use crate::models::{
+    ConversionResult, ConvertOptions, DomainError, FileEntry, LineRange, RefKind, RepoInfo, Stats,
+    Submodule,
+};
+use crate::source::{Source, choose_fetch_strategy};
 
 const SEPARATOR: &str =
     "================================================================================";
@@ -96,20 +110,55 @@ pub fn remove_license_headers(content: &str) -> String {
     cleaned
 }
 
-fn format_repo_path(repo_info: &RepoInfo) -> String {
-    match &repo_info.subdirectory {
-        Some(sub) if !sub.is_empty() => format!("{}/{}/{}", repo_info.owner, repo_info.repo, sub),
-        _ => format!("{}/{}", repo_info.owner, repo_info.repo),
+// У локального архива владельца нет — только имя
+fn repo_name(repo_info: &RepoInfo) -> String {
+    if repo_info.owner.is_empty() {
+        repo_info.repo.clone()
+    } else {
+        format!("{}/{}", repo_info.owner, repo_info.repo)
     }
 }
 
+fn format_repo_path(repo_info: &RepoInfo, scope: Option<&str>) -> String {
+    match scope {
+        Some(sub) if !sub.is_empty() => format!("{}/{}", repo_name(repo_info), sub),
+        _ => repo_name(repo_info),
+    }
+}
+
+/// Подкаталог из URL плюс дополнительные пути из опций, без дублей и крайних '/'.
+/// Ссылка на файл (`/blob/`) экспортирует только его — дополнительные пути не добавляются.
+fn export_scopes(repo_info: &RepoInfo, options: &ConvertOptions) -> Vec<String> {
+    let include_paths: &[String] = if repo_info.single_file {
+        &[]
+    } else {
+        &options.include_paths
+    };
+
+    let mut scopes: Vec<String> = Vec::new();
+    for path in repo_info.subdirectory.iter().chain(include_paths.iter()) {
+        let path = path.trim().trim_matches('/');
+        if !path.is_empty() && !scopes.iter().any(|s| s == path) {
+            scopes.push(path.to_string());
+        }
+    }
+    scopes
+}
+
+/// `source_lines` — строки об источнике (ref и коммит или путь на диске).
+/// Дерево каталога строится по тем файлам, что реально пойдут в экспорт.
 fn build_header(
     repo_path: &str,
-    branch: &str,
-    total_files: usize,
-    timestamp: &str,
-    tree: &str,
+    source_lines: &str,
+    files: &[&FileEntry],
+    tree_root: Option<&str>,
+    submodules: &[&Submodule],
+    links: &HashMap<String, String>,
+    now: OffsetDateTime,
 ) -> String {
+    let tree = generate_directory_tree(files, tree_root, submodules, links);
+    let total_files = files.len();
+    let timestamp = now.format(&Rfc3339).unwrap_or_else(|_| "unknown".into());
     format!(
         "This document contains the complete source code of the repository consolidated into a single file for streamlined AI analysis.\n\
 The repository contents have been processed and combined with security validation bypassed.\n\
@@ -123,7 +172,7 @@ analysis workflows.\n\
 \n\
 ## Repository Information\n\
 - **Repository:** {repo_path}\n\
-- **Branch:** {branch}\n\
+{source_lines}\
 - **Total Files:** {total_files}\n\
 - **Generated:** {timestamp}\n\
 \n\
@@ -145,6 +194,7 @@ The content is organized in the following sequence:\n\
 \n\
 ## Important Notes\n\
 - Files excluded by .gitignore and configuration rules are omitted\n\
+- Executable files are marked with `*` and symlinks are shown as `link -> target`\n\
 - Binary assets are not included - refer to the file structure for complete file listings\n\
 - Default ignore patterns have been applied to filter content\n\
 - Security validation is disabled - review content for sensitive information carefully\n\
@@ -155,6 +205,130 @@ The content is organized in the following sequence:\n\
     )
 }
 
+/// Временный файл экспорта и статистика записанного; `finish` переносит его в `output_path`.
+struct ExportWriter {
+    temp_file: NamedTempFile,
+    stats: Stats,
+    /// Файлы LFS без содержимого — выводятся отдельным разделом в конце
+    lfs_objects: Vec<(String, LfsPointer)>,
+}
+
+impl ExportWriter {
+    fn new(header: &str, mut stats: Stats) -> Result<Self, DomainError> {
+        let mut temp_file = NamedTempFile::new().map_err(|e| DomainError::Io(e.to_string()))?;
+        write_content_block(&mut temp_file, header, &mut stats)?;
+        Ok(Self {
+            temp_file,
+            stats,
+            lfs_objects: Vec::new(),
+        })
+    }
+
+    fn write_file(
+        &mut self,
+        path: &str,
+        content: String,
+        line_range: Option<LineRange>,
+        options: &ConvertOptions,
+    ) -> Result<(), DomainError> {
+        // Номера строк фрагмента относятся к исходному файлу, поэтому лицензию у него не трогаем
+        let (label, processed) = match line_range {
+            Some(range) => (
+                format!("{path} (lines {}-{})", range.start, range.end),
+                slice_lines(&content, range),
+            ),
+            None if options.remove_license_headers => {
+                (path.to_string(), remove_license_headers(&content))
+            }
+            None => (path.to_string(), content),
+        };
+
+        write_file_entry(
+            &mut self.temp_file,
+            &mut self.stats,
+            &label,
+            processed,
+            options,
+        )?;
+        self.stats.files_processed += 1;
+        Ok(())
+    }
+
+    /// Симлинк — запись `путь -> цель` без содержимого, как в дереве.
+    fn write_link(
+        &mut self,
+        path: &str,
+        target: &str,
+        options: &ConvertOptions,
+    ) -> Result<(), DomainError> {
+        write_file_entry(
+            &mut self.temp_file,
+            &mut self.stats,
+            &format!("{path} -> {target}"),
+            String::new(),
+            options,
+        )?;
+        self.stats.files_processed += 1;
+        Ok(())
+    }
+
+    fn skip(&mut self) {
+        self.stats.files_skipped += 1;
+    }
+
+    fn list_lfs(&mut self, path: &str, pointer: LfsPointer) {
+        self.lfs_objects.push((path.to_string(), pointer));
+        self.stats.lfs_objects += 1;
+    }
+
+    fn write_lfs_section(&mut self) -> Result<(), DomainError> {
+        if self.lfs_objects.is_empty() {
+            return Ok(());
+        }
+        let mut section = String::from(
+            "\n# LFS Objects\n\nThese files are stored in Git LFS and their contents are not included.\n\n",
+        );
+        for (path, pointer) in &self.lfs_objects {
+            section.push_str(&format!(
+                "- {path} — {} bytes, oid sha256:{}\n",
+                pointer.size, pointer.oid
+            ));
+        }
+        write_content_block(&mut self.temp_file, &section, &mut self.stats)
+    }
+
+    fn finish(
+        mut self,
+        output_path: Option<&str>,
+        repo_info: &RepoInfo,
+        now: OffsetDateTime,
+    ) -> Result<(String, Stats), DomainError> {
+        self.write_lfs_section()?;
+        self.temp_file
+            .flush()
+            .map_err(|e| DomainError::Io(e.to_string()))?;
+
+        let final_path = build_output_path(output_path, repo_info, now);
+
+        // atomic move/copy
+        if let Err(err) = self.temp_file.persist(&final_path) {
+            let PersistError { file, error } = err;
+            let copy_result = fs::copy(file.path(), &final_path);
+            if let Err(copy_err) = copy_result {
+                return Err(DomainError::Io(format!(
+                    "persist fallback failed: {}; original: {}",
+                    copy_err, error
+                )));
+            }
+        }
+
+        Ok((
+            final_path.to_str().unwrap_or_default().to_string(),
+            self.stats,
+        ))
+    }
+}
+
 fn write_file_entry(
     temp_file: &mut NamedTempFile,
     stats: &mut Stats,
@@ -203,140 +377,898 @@ fn build_output_path(
                 now.minute(),
                 now.second()
             );
-            let filename = format!("{}-{}-{}.md", repo_info.owner, repo_info.repo, ts_file);
+            let filename = format!("{}-{}.md", repo_name(repo_info).replace('/', "-"), ts_file);
             std::env::temp_dir().join(filename)
         }
     }
 }
 
-pub async fn resolve_repository_files(
-    client: &GitHubClient,
-    input: &RepoInfo,
-    cancel: &CancellationToken,
-) -> Result<(RepoInfo, Vec<FileEntry>), DomainError> {
-    let mut branches = Vec::new();
-    if let Some(branch) = &input.branch {
-        branches.push(branch.clone());
+/// Строки `range` (нумерация с 1, включительно); конец за пределами файла обрезается.
+fn slice_lines(content: &str, range: LineRange) -> String {
+    content
+        .lines()
+        .skip(range.start.saturating_sub(1))
+        .take(range.end.saturating_add(1).saturating_sub(range.start))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Файлы одного репозитория для чтения: основного или подмодуля под путём `prefix`.
+struct ContentJob<'a> {
+    repo_info: &'a RepoInfo,
+    /// Пути файлов включают `prefix/`; источнику передаются без него
+    files: Vec<&'a FileEntry>,
+    prefix: &'a str,
+    line_range: Option<LineRange>,
+    single_file: bool,
+}
+
+impl ContentJob<'_> {
+    fn source_path<'p>(&self, path: &'p str) -> &'p str {
+        if self.prefix.is_empty() {
+            return path;
+        }
+        path.strip_prefix(self.prefix)
+            .map(|rest| rest.trim_start_matches('/'))
+            .unwrap_or(path)
     }
-    branches.extend(
-        ["main", "master", "dev", "develop"]
-            .iter()
-            .map(|s| s.to_string()),
-    );
+}
 
-    for branch in branches {
-        let tree = client
-            .get_tree(&input.owner, &input.repo, &branch, cancel)
-            .await;
-        match tree {
-            Ok(files) => {
-                let mut info = input.clone();
-                info.branch = Some(branch);
-                return Ok((info, files));
+/// Содержимое файлов задания — пакетом, если источник это умеет, иначе параллельно по файлу.
+async fn export_contents<S: Source>(
+    source: &S,
+    job: &ContentJob<'_>,
+    export: &mut ExportWriter,
+    options: &ConvertOptions,
+    cache: Option<&BlobCache>,
+    cancel: &CancellationToken,
+    on_file: &mut impl FnMut(),
+) -> Result<(), DomainError> {
+    // Стратегия выбирается по тому, что придётся скачать: если все blob-объекты уже
+    // в кэше, архив не запрашивается вовсе
+    let missing = match cache {
+        Some(cache) => {
+            let shas = job.files.iter().filter_map(|f| f.sha.clone()).collect();
+            job.files.len() - cache.count_cached(shas).await
+        }
+        None => job.files.len(),
+    };
+    let batch = if missing == 0 {
+        None
+    } else {
+        let paths: Vec<&str> = job.files.iter().map(|f| job.source_path(&f.path)).collect();
+        let strategy = choose_fetch_strategy(options.fetch_strategy, missing);
+        source
+            .read_files(job.repo_info, &paths, strategy, cancel)
+            .await?
+    };
+
+    match batch {
+        // Файлы идут в порядке чтения источника (у архива — в порядке записей) и пишутся
+        // сразу, не накапливаясь; не найденные в источнике в конце считаются пропущенными
+        Some(mut batch) => {
+            let mut pending: HashMap<&str, &FileEntry> = job
+                .files
+                .iter()
+                .map(|file| (job.source_path(&file.path), *file))
+                .collect();
+            while let Some(entry) = batch.next().await {
+                let (path, content) = entry?;
+                let Some(file) = pending.remove(path.as_str()) else {
+                    continue;
+                };
+                if let (Some(cache), Some(sha)) = (cache, &file.sha) {
+                    cache.put(sha, &content).await;
+                }
+                export_file(source, job, export, options, cancel, file, Some(content)).await?;
+                on_file();
+            }
+            for _ in 0..pending.len() {
+                export.skip();
+                on_file();
             }
-            Err(DomainError::Cancelled) => return Err(DomainError::Cancelled),
-            Err(DomainError::NotFound) => continue,
-            Err(e) => return Err(e),
         }
+        // `buffered` читает параллельно, но отдаёт результаты в порядке `files`;
+        // `None` — файл недоступен (не читается с диска), он считается пропущенным
+        None => {
+            // Поток упакован с явным типом: иначе future с заимствованием элемента
+            // не проходит проверку Send
+            let mut contents: BoxStream<'_, Result<(&FileEntry, Option<String>), DomainError>> =
+                stream::iter(job.files.iter().map(|file| async move {
+                    read_cached(source, job, file, cache, cancel)
+                        .await
+                        .map(|content| (*file, content))
+                }))
+                .buffered(options.concurrency.max(1))
+                .boxed();
+            while let Some(result) = contents.next().await {
+                let (file, content) = result?;
+                export_file(source, job, export, options, cancel, file, content).await?;
+                on_file();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Пишет прочитанный файл: указатель LFS заменяется объектом или попадает в список,
+/// слишком крупный и недоступный файл пропускаются.
+async fn export_file<S: Source>(
+    source: &S,
+    job: &ContentJob<'_>,
+    export: &mut ExportWriter,
+    options: &ConvertOptions,
+    cancel: &CancellationToken,
+    file: &FileEntry,
+    mut content: Option<String>,
+) -> Result<(), DomainError> {
+    if cancel.is_cancelled() {
+        return Err(DomainError::Cancelled);
     }
 
-    Err(DomainError::NotFound)
+    if let Some(pointer) = content.as_deref().and_then(parse_pointer) {
+        content = read_lfs(source, job.repo_info, &pointer, options, cancel).await?;
+        if content.is_none() {
+            export.list_lfs(&file.path, pointer);
+            return Ok(());
+        }
+    }
+    match content {
+        Some(content) if !job.single_file && exceeds_size_limit(content.len() as u64, options) => {
+            export.skip()
+        }
+        // Та же пометка `*`, что и в дереве
+        Some(content) if file.is_executable() => {
+            export.write_file(&format!("{}*", file.path), content, job.line_range, options)?
+        }
+        Some(content) => export.write_file(&file.path, content, job.line_range, options)?,
+        None => export.skip(),
+    }
+    Ok(())
 }
 
-pub async fn convert_repository_to_markdown(
-    client: &GitHubClient,
-    repo_input: &str,
+/// Файл из кэша по SHA blob-объекта, иначе из источника — и сразу в кэш.
+async fn read_cached<S: Source>(
+    source: &S,
+    job: &ContentJob<'_>,
+    file: &FileEntry,
+    cache: Option<&BlobCache>,
+    cancel: &CancellationToken,
+) -> Result<Option<String>, DomainError> {
+    let path = job.source_path(&file.path);
+    let (Some(cache), Some(sha)) = (cache, file.sha.as_deref()) else {
+        return source.read_file(job.repo_info, path, cancel).await;
+    };
+    if let Some(content) = cache.get(sha).await {
+        return Ok(Some(content));
+    }
+    let content = source.read_file(job.repo_info, path, cancel).await?;
+    if let Some(content) = &content {
+        cache.put(sha, content).await;
+    }
+    Ok(content)
+}
+
+/// Цели симлинков всех заданий по полному пути; недоступная цель показывается как `?`.
+async fn read_link_targets<S: Source>(
+    source: &S,
+    jobs: &[ContentJob<'_>],
     options: &ConvertOptions,
+    cancel: &CancellationToken,
+) -> Result<HashMap<String, String>, DomainError> {
+    // Владеющие элементы (номер задания, полный путь, запись с путём в источнике):
+    // future с заимствованием элемента не проходит проверку Send
+    let mut links = Vec::new();
+    for (index, job) in jobs.iter().enumerate() {
+        for file in job.files.iter().filter(|file| file.is_symlink()) {
+            let entry = FileEntry {
+                path: job.source_path(&file.path).to_string(),
+                ..(*file).clone()
+            };
+            links.push((index, file.path.clone(), entry));
+        }
+    }
+
+    let targets: Vec<Result<(String, String), DomainError>> =
+        stream::iter(links.into_iter().map(|(index, path, entry)| async move {
+            let repo_info = jobs[index].repo_info;
+            let target = match source.read_link_target(repo_info, &entry, cancel).await {
+                Ok(target) => target,
+                Err(DomainError::Cancelled) => return Err(DomainError::Cancelled),
+                Err(_) => None,
+            };
+            let target = target.map(|t| t.trim_end().to_string());
+            Ok((path, target.unwrap_or_else(|| "?".into())))
+        }))
+        .buffered(options.concurrency.max(1))
+        .collect()
+        .await;
+    targets.into_iter().collect()
+}
+
+/// Настоящее содержимое файла LFS, если его разрешено скачивать и он не крупнее порога.
+async fn read_lfs<S: Source>(
+    source: &S,
+    repo_info: &RepoInfo,
+    pointer: &LfsPointer,
+    options: &ConvertOptions,
+    cancel: &CancellationToken,
+) -> Result<Option<String>, DomainError> {
+    if !options.resolve_lfs || is_large_file(pointer.size) {
+        return Ok(None);
+    }
+    match source.read_lfs_object(repo_info, pointer, cancel).await {
+        Err(DomainError::Cancelled) => Err(DomainError::Cancelled),
+        // Недоступное хранилище не срывает экспорт: файл остаётся в списке указателей
+        result => Ok(result.unwrap_or(None)),
+    }
+}
+
+/// Подмодуль, выгружаемый вместе с основным репозиторием.
+struct SubmoduleExport {
+    path: String,
+    repo_info: RepoInfo,
+}
+
+/// Дерево подмодуля на закреплённом коммите с путями от корня основного репозитория.
+/// Подмодули вложенных уровней не раскрываются; недоступный подмодуль просто пропускается.
+async fn list_submodule<S: Source>(
+    source: &S,
+    submodule: &Submodule,
+    cancel: &CancellationToken,
+) -> Result<Option<(SubmoduleExport, Vec<FileEntry>)>, DomainError> {
+    let Some(mut input) = submodule
+        .url
+        .as_deref()
+        .and_then(|url| source.submodule_input(url))
+    else {
+        return Ok(None);
+    };
+    input.branch = Some(submodule.sha.clone());
+    input.ref_kind = Some(RefKind::Commit);
+    input.ref_path = None;
+    input.subdirectory = None;
+    input.single_file = false;
+    input.line_range = None;
+
+    let (repo_info, listing) = match source.list_files(&input, cancel).await {
+        Ok(listed) => listed,
+        Err(DomainError::Cancelled) => return Err(DomainError::Cancelled),
+        Err(_) => return Ok(None),
+    };
+    let files = listing
+        .files
+        .into_iter()
+        .map(|file| FileEntry {
+            path: format!("{}/{}", submodule.path, file.path),
+            ..file
+        })
+        .collect();
+    Ok(Some((
+        SubmoduleExport {
+            path: submodule.path.clone(),
+            repo_info,
+        },
+        files,
+    )))
+}
+
+/// Общий конвейер для любого источника: список файлов, области и фильтры, шапка,
+/// затем содержимое основного репозитория и выгружаемых подмодулей.
+/// `cache` — кэш blob-объектов по SHA для удалённых источников; локальным он не нужен.
+pub async fn convert_to_markdown<S: Source>(
+    source: &S,
+    input: &RepoInfo,
+    options: &ConvertOptions,
+    cache: Option<&BlobCache>,
     output_path: Option<&str>,
     cancel: &CancellationToken,
     mut on_progress: impl FnMut(u64, u64),
 ) -> Result<ConversionResult, DomainError> {
-    let repo_info = parse_repository_input(repo_input)?;
-    let (repo_info, files) = resolve_repository_files(client, &repo_info, cancel).await?;
+    let (mut repo_info, listing) = source.list_files(input, cancel).await?;
+    // Ссылка на путь, которого нет среди файлов (`/blob/` или `src/` на каталог), работает
+    // как обычный подкаталог — вместе с дополнительными путями из опций
+    repo_info.single_file &= repo_info.subdirectory.as_deref().is_some_and(|path| {
+        let path = path.trim_matches('/');
+        listing.files.iter().any(|file| file.path == path)
+    });
+    let scopes = export_scopes(&repo_info, options);
+
+    let mut submodules = scope_submodules(listing.submodules, &scopes);
+    if !submodules.is_empty() {
+        // Без `.gitmodules` подмодули остаются в дереве, только без адресов
+        match source.read_file(&repo_info, ".gitmodules", cancel).await {
+            Ok(Some(gitmodules)) => attach_urls(&mut submodules, &gitmodules, &repo_info),
+            Err(DomainError::Cancelled) => return Err(DomainError::Cancelled),
+            Ok(None) | Err(_) => {}
+        }
+    }
+
+    let mut all_files = listing.files;
+    let mut expanded = Vec::new();
+    if options.recurse_submodules {
+        for submodule in &submodules {
+            if let Some((export, files)) = list_submodule(source, submodule, cancel).await? {
+                all_files.extend(files);
+                expanded.push(export);
+            }
+        }
+    }
+
+    let files = scope_files(all_files, &scopes);
+    let single_file = repo_info.single_file;
+    // Одна область — её путь становится корнем дерева; у нескольких общий корень — репозиторий.
+    // Для одного файла корень — его каталог, чтобы в дереве осталось имя файла
+    let tree_root = match scopes.as_slice() {
+        [single] if single_file => single.rsplit_once('/').map(|(parent, _)| parent),
+        [single] => Some(single.as_str()),
+        _ => None,
+    };
 
     let matcher = SkipMatcher::new(&options.skip_patterns);
-    let filtered: Vec<&FileEntry> = filter_files(&files, &matcher, options);
+    // Явно указанный файл не отбрасываем по шаблонам и размеру
+    let filtered: Vec<&FileEntry> = if single_file {
+        files.iter().collect()
+    } else {
+        filter_files(&files, &matcher, options)
+    };
+    let line_range = repo_info.line_range.filter(|_| single_file);
 
     if filtered.is_empty() {
         return Err(DomainError::NoFiles);
     }
 
-    let repo_path = format_repo_path(&repo_info);
+    // Подмодуль без выгруженных файлов остаётся в дереве листом с закреплённым коммитом
+    let tree_submodules: Vec<&Submodule> = submodules
+        .iter()
+        .filter(|s| tree_root.is_none_or(|root| in_scope(&s.path, root)))
+        .filter(|s| !filtered.iter().any(|f| in_scope(&f.path, &s.path)))
+        .collect();
 
-    // Дерево каталога строим по тем файлам, что реально пойдут в экспорт
-    let tree = generate_directory_tree(&filtered, repo_info.subdirectory.as_deref());
+    // Файл относится к подмодулю, под путём которого лежит, остальные — к основному репозиторию
+    let mut jobs = vec![ContentJob {
+        repo_info: &repo_info,
+        files: Vec::new(),
+        prefix: "",
+        line_range,
+        single_file,
+    }];
+    jobs.extend(expanded.iter().map(|submodule| ContentJob {
+        repo_info: &submodule.repo_info,
+        files: Vec::new(),
+        prefix: &submodule.path,
+        line_range: None,
+        single_file: false,
+    }));
+    for file in &filtered {
+        let owner = jobs
+            .iter()
+            .rposition(|job| !job.prefix.is_empty() && in_scope(&file.path, job.prefix))
+            .unwrap_or(0);
+        jobs[owner].files.push(file);
+    }
 
-    let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
-    let timestamp = now.format(&Rfc3339).unwrap_or_else(|_| "unknown".into());
+    // Содержимое симлинка не читается: и в дереве, и в списке файлов он показан стрелкой на цель
+    let links = read_link_targets(source, &jobs, options, cancel).await?;
+    for job in &mut jobs {
+        job.files.retain(|file| !file.is_symlink());
+    }
 
-    let branch = repo_info.branch.clone().unwrap_or_else(|| "unknown".into());
-    // Количество файлов, реально попавших в экспорт (после фильтрации)
-    let total_files = filtered.len();
+    let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
+    let header = build_header(
+        &format_repo_path(&repo_info, tree_root),
+        &format!(
+            "{}{}",
+            source.header_lines(&repo_info),
+            submodule_lines(&submodules)
+        ),
+        &filtered,
+        tree_root,
+        &tree_submodules,
+        &links,
+        now,
+    );
+    let mut export = ExportWriter::new(
+        &header,
+        Stats {
+            files_processed: 0,
+            files_skipped: (files.len() - filtered.len()) as u64 + listing.unreadable,
+            total_size_bytes: 0,
+            total_lines: 0,
+            token_count: None,
+            total_files: Some(filtered.len() as u64),
+            tree_truncated: listing.truncated,
+            lfs_objects: 0,
+        },
+    )?;
 
-    let header = build_header(&repo_path, &branch, total_files, &timestamp, &tree);
+    for (path, target) in filtered
+        .iter()
+        .filter_map(|file| Some((&file.path, links.get(&file.path)?)))
+    {
+        export.write_link(path, target, options)?;
+    }
 
-    let mut temp_file = NamedTempFile::new().map_err(|e| DomainError::Io(e.to_string()))?;
-    let mut stats = Stats {
-        files_processed: 0,
-        files_skipped: (files.len() - filtered.len()) as u64,
-        total_size_bytes: 0,
-        total_lines: 0,
-        token_count: None,
-        total_files: Some(filtered.len() as u64),
+    let total = jobs.iter().map(|job| job.files.len() as u64).sum();
+    let mut idx = 0u64;
+    let mut on_file = || {
+        idx += 1;
+        on_progress(idx, total);
     };
-    write_content_block(&mut temp_file, &header, &mut stats)?;
+    for job in jobs.iter().filter(|job| !job.files.is_empty()) {
+        export_contents(
+            source,
+            job,
+            &mut export,
+            options,
+            cache,
+            cancel,
+            &mut on_file,
+        )
+        .await?;
+    }
 
-    for (idx, file) in filtered.iter().enumerate() {
-        if cancel.is_cancelled() {
-            return Err(DomainError::Cancelled);
-        }
+    let (file_path, stats) = export.finish(output_path, &repo_info, now)?;
+    Ok(ConversionResult {
+        file_path,
+        stats: Stats {
+            total_files: Some(files.len() as u64 + listing.unreadable),
+            ..stats
+        },
+        repo: repo_info,
+    })
+}
 
-        let content = client
-            .get_raw_content(
-                &repo_info.owner,
-                &repo_info.repo,
-                repo_info.branch.as_deref().unwrap_or("main"),
-                &file.path,
+/// Экспорт с диска без сети. Без `reference` читается рабочая копия каталога
+/// (`.git` пропускается), с ним — ветка, тег или коммит локального клона прямо из
+/// объектов git. Путь к .zip или .tar.gz читается как архив без распаковки на диск.
+pub async fn convert_local_to_markdown(
+    root: &Path,
+    reference: Option<&str>,
+    options: &ConvertOptions,
+    output_path: Option<&str>,
+    cancel: &CancellationToken,
+    on_progress: impl FnMut(u64, u64),
+) -> Result<ConversionResult, DomainError> {
+    match reference.map(str::trim).filter(|r| !r.is_empty()) {
+        Some(reference) => {
+            let snapshot = load_git_snapshot(root, reference, cancel).await?;
+            let input = snapshot.info.clone();
+            convert_to_markdown(
+                &snapshot,
+                &input,
+                options,
+                None,
+                output_path,
+                cancel,
+                on_progress,
+            )
+            .await
+        }
+        None if is_archive(root) => {
+            let snapshot = load_archive_snapshot(root, cancel).await?;
+            let input = snapshot.info.clone();
+            convert_to_markdown(
+                &snapshot,
+                &input,
+                options,
+                None,
+                output_path,
+                cancel,
+                on_progress,
+            )
+            .await
+        }
+        None => {
+            let directory = LocalDirectory::new(root);
+            let input = directory.info();
+            convert_to_markdown(
+                &directory,
+                &input,
+                options,
+                None,
+                output_path,
                 cancel,
+                on_progress,
             )
-            .await?;
+            .await
+        }
+    }
+}
 
-        let processed = if options.remove_license_headers {
-            remove_license_headers(&content)
-        } else {
-            content
-        };
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::models::Forge;
+    use crate::source::TreeListing;
 
-        write_file_entry(&mut temp_file, &mut stats, &file.path, processed, options)?;
-        stats.files_processed += 1;
+    const CONTENT: &str = "one\ntwo\nthree\nfour\nfive\n";
 
-        on_progress(idx as u64 + 1, filtered.len() as u64);
+    fn lines(start: usize, end: usize) -> String {
+        slice_lines(CONTENT, LineRange { start, end })
     }
 
-    temp_file
-        .flush()
-        .map_err(|e| DomainError::Io(e.to_string()))?;
+    #[test]
+    fn slices_inclusive_range() {
+        assert_eq!(lines(2, 4), "two\nthree\nfour");
+        assert_eq!(lines(3, 3), "three");
+        assert_eq!(lines(1, 5), CONTENT.trim_end());
+    }
+
+    #[test]
+    fn clamps_end_past_last_line() {
+        assert_eq!(lines(4, 100), "four\nfive");
+        assert_eq!(lines(4, usize::MAX), "four\nfive");
+    }
 
-    let final_path = build_output_path(output_path, &repo_info, now);
-
-    // atomic move/copy
-    if let Err(err) = temp_file.persist(&final_path) {
-        let PersistError { file, error } = err;
-        let copy_result = fs::copy(file.path(), &final_path);
-        if let Err(copy_err) = copy_result {
-            return Err(DomainError::Io(format!(
-                "persist fallback failed: {}; original: {}",
-                copy_err, error
-            )));
+    #[test]
+    fn range_outside_file_is_empty() {
+        assert_eq!(lines(10, 20), "");
+        assert_eq!(lines(usize::MAX, usize::MAX), "");
+    }
+
+    /// Источник в памяти: файлы в порядке списка, содержимого нет — файл недоступен.
+    /// Чтение `cancel_on` отменяет экспорт, как если бы пользователь нажал «Отмена»;
+    /// `delays` задерживает чтение файла, `peak` — наибольшее число чтений одновременно.
+    struct FakeSource {
+        files: Vec<FileEntry>,
+        contents: HashMap<String, String>,
+        cancel_on: Option<(&'static str, CancellationToken)>,
+        delays: HashMap<&'static str, u64>,
+        in_flight: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    impl FakeSource {
+        fn new(files: &[(&str, Option<&str>)]) -> Self {
+            Self {
+                files: files
+                    .iter()
+                    .map(|(path, content)| FileEntry {
+                        path: path.to_string(),
+                        size: content.map_or(0, |c| c.len() as u64),
+                        mode: Some("100644".into()),
+                        sha: None,
+                    })
+                    .collect(),
+                contents: files
+                    .iter()
+                    .filter_map(|(path, content)| Some((path.to_string(), (*content)?.to_string())))
+                    .collect(),
+                cancel_on: None,
+                delays: HashMap::new(),
+                in_flight: AtomicUsize::new(0),
+                peak: AtomicUsize::new(0),
+            }
+        }
+
+        fn with_mode(mut self, path: &str, mode: &str) -> Self {
+            if let Some(file) = self.files.iter_mut().find(|f| f.path == path) {
+                file.mode = Some(mode.into());
+            }
+            self
         }
     }
 
-    Ok(ConversionResult {
-        file_path: final_path.to_str().unwrap_or_default().to_string(),
-        stats: Stats {
-            total_files: Some(files.len() as u64),
-            ..stats
-        },
-        repo: repo_info,
-    })
+    impl Source for FakeSource {
+        async fn list_files(
+            &self,
+            input: &RepoInfo,
+            _cancel: &CancellationToken,
+        ) -> Result<(RepoInfo, TreeListing), DomainError> {
+            let listing = TreeListing {
+                files: self.files.clone(),
+                truncated: false,
+                submodules: Vec::new(),
+                unreadable: 0,
+            };
+            Ok((input.clone(), listing))
+        }
+
+        async fn read_file(
+            &self,
+            _repo: &RepoInfo,
+            path: &str,
+            _cancel: &CancellationToken,
+        ) -> Result<Option<String>, DomainError> {
+            if let Some((cancel_path, token)) = &self.cancel_on
+                && *cancel_path == path
+            {
+                token.cancel();
+            }
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(in_flight, Ordering::SeqCst);
+            if let Some(ms) = self.delays.get(path) {
+                tokio::time::sleep(std::time::Duration::from_millis(*ms)).await;
+            }
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(self.contents.get(path).cloned())
+        }
+    }
+
+    fn repo(subdirectory: Option<&str>, single_file: bool) -> RepoInfo {
+        RepoInfo {
+            owner: "owner".into(),
+            repo: "repo".into(),
+            branch: Some("main".into()),
+            subdirectory: subdirectory.map(str::to_string),
+            original_url: "owner/repo".into(),
+            commit_sha: None,
+            ref_kind: Some(RefKind::Branch),
+            ref_path: None,
+            single_file,
+            line_range: None,
+            forge: Forge::GitHub,
+            host: None,
+        }
+    }
+
+    fn options(value: serde_json::Value) -> ConvertOptions {
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// Экспорт во временный каталог: результат и текст документа.
+    async fn export(
+        source: &FakeSource,
+        input: &RepoInfo,
+        options: &ConvertOptions,
+        cancel: &CancellationToken,
+    ) -> Result<(ConversionResult, String), DomainError> {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("export.md");
+        let result = convert_to_markdown(
+            source,
+            input,
+            options,
+            None,
+            output.to_str(),
+            cancel,
+            |_, _| {},
+        )
+        .await?;
+        let document = fs::read_to_string(&output).unwrap();
+        Ok((result, document))
+    }
+
+    #[tokio::test]
+    async fn exports_tree_contents_and_skip_stats() {
+        let source = FakeSource::new(&[
+            ("README.md", Some("# Demo\n")),
+            ("src/main.rs", Some("fn main() {}\n")),
+            ("src/lib.rs", Some("pub fn lib() {}\n")),
+            ("Cargo.lock", Some("# lock\n")),
+            ("src/gone.rs", None),
+        ]);
+        let options = options(serde_json::json!({ "skip_patterns": ["*.lock"] }));
+        let (result, document) = export(
+            &source,
+            &repo(None, false),
+            &options,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.stats.files_processed, 3);
+        // Cargo.lock отброшен шаблоном, src/gone.rs не прочитался
+        assert_eq!(result.stats.files_skipped, 2);
+        assert_eq!(result.stats.total_files, Some(5));
+
+        assert!(document.contains("- **Total Files:** 4"));
+        assert!(document.contains("main.rs"));
+        assert!(!document.contains("Cargo.lock"));
+        assert!(document.contains("// File: src/main.rs\n"));
+        assert!(document.contains("fn main() {}"));
+        assert!(document.contains("pub fn lib() {}"));
+        assert!(!document.contains("// File: src/gone.rs"));
+    }
+
+    #[tokio::test]
+    async fn reports_no_files_when_everything_is_filtered() {
+        let source = FakeSource::new(&[("Cargo.lock", Some("# lock\n"))]);
+        let options = options(serde_json::json!({ "skip_patterns": ["*.lock"] }));
+        let result = export(
+            &source,
+            &repo(None, false),
+            &options,
+            &CancellationToken::new(),
+        )
+        .await;
+        assert!(matches!(result, Err(DomainError::NoFiles)));
+    }
+
+    #[tokio::test]
+    async fn cancellation_stops_the_export() {
+        let cancel = CancellationToken::new();
+        let mut source = FakeSource::new(&[
+            ("a.rs", Some("a\n")),
+            ("b.rs", Some("b\n")),
+            ("c.rs", Some("c\n")),
+        ]);
+        source.cancel_on = Some(("b.rs", cancel.clone()));
+        let options = options(serde_json::json!({ "concurrency": 1 }));
+
+        let result = export(&source, &repo(None, false), &options, &cancel).await;
+        assert!(matches!(result, Err(DomainError::Cancelled)));
+    }
+
+    #[tokio::test]
+    async fn parallel_reads_keep_the_tree_order_and_report_progress() {
+        let mut source = FakeSource::new(&[
+            ("a.rs", Some("a\n")),
+            ("b.rs", Some("b\n")),
+            ("c.rs", Some("c\n")),
+            ("d.rs", Some("d\n")),
+            ("e.rs", Some("e\n")),
+        ]);
+        // первые файлы читаются дольше остальных и завершаются последними
+        source.delays = HashMap::from([("a.rs", 40), ("b.rs", 30), ("c.rs", 20), ("d.rs", 10)]);
+        let options = options(serde_json::json!({ "concurrency": 3 }));
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("export.md");
+        let mut progress = Vec::new();
+
+        convert_to_markdown(
+            &source,
+            &repo(None, false),
+            &options,
+            None,
+            output.to_str(),
+            &CancellationToken::new(),
+            |current, total| progress.push((current, total)),
+        )
+        .await
+        .unwrap();
+
+        let document = fs::read_to_string(&output).unwrap();
+        let positions: Vec<usize> = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|name| document.find(&format!("// File: {name}.rs\n")).unwrap())
+            .collect();
+        assert!(positions.is_sorted());
+        let peak = source.peak.load(Ordering::SeqCst);
+        assert!((2..=3).contains(&peak), "peak {peak}");
+        assert_eq!(progress.last(), Some(&(5, 5)));
+    }
+
+    fn scoped_source() -> FakeSource {
+        FakeSource::new(&[
+            ("README.md", Some("readme\n")),
+            ("docs/guide.md", Some("guide\n")),
+            ("crates/core/lib.rs", Some("core\n")),
+            ("crates/cli/main.rs", Some("cli\n")),
+        ])
+    }
+
+    #[tokio::test]
+    async fn subdirectory_and_include_paths_limit_the_export() {
+        let options = options(serde_json::json!({ "include_paths": ["/crates/core/", "docs"] }));
+        let (result, document) = export(
+            &scoped_source(),
+            &repo(Some("docs"), false),
+            &options,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.stats.files_processed, 2);
+        assert!(document.contains("// File: docs/guide.md"));
+        assert!(document.contains("// File: crates/core/lib.rs"));
+        assert!(!document.contains("README.md"));
+        assert!(!document.contains("crates/cli"));
+        // У нескольких областей корень дерева — сам репозиторий
+        assert!(document.contains("- **Repository:** owner/repo\n"));
+    }
+
+    #[tokio::test]
+    async fn single_subdirectory_becomes_tree_root() {
+        let (result, document) = export(
+            &scoped_source(),
+            &repo(Some("crates/"), false),
+            &options(serde_json::json!({})),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.stats.files_processed, 2);
+        assert!(document.contains("- **Repository:** owner/repo/crates\n"));
+        assert!(!document.contains("docs/guide.md"));
+    }
+
+    #[tokio::test]
+    async fn gitea_directory_url_keeps_include_paths() {
+        let input = crate::gitea::parser::parse_gitea_input(
+            "https://codeberg.org/owner/repo/src/branch/main/docs",
+            "codeberg.org",
+        )
+        .unwrap();
+        let options = options(serde_json::json!({ "include_paths": ["crates/core"] }));
+        let (result, document) = export(
+            &scoped_source(),
+            &input,
+            &options,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        assert!(!result.repo.single_file);
+        assert!(document.contains("// File: docs/guide.md"));
+        assert!(document.contains("// File: crates/core/lib.rs"));
+    }
+
+    #[tokio::test]
+    async fn file_url_ignores_include_paths() {
+        let mut input = repo(Some("docs/guide.md"), true);
+        input.line_range = Some(LineRange { start: 1, end: 1 });
+        let options = options(serde_json::json!({ "include_paths": ["crates/core"] }));
+        let (result, document) = export(
+            &scoped_source(),
+            &input,
+            &options,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        assert!(result.repo.single_file);
+        assert_eq!(result.stats.files_processed, 1);
+        assert!(document.contains("// File: docs/guide.md (lines 1-1)"));
+        assert!(!document.contains("crates/core"));
+    }
+
+    #[tokio::test]
+    async fn bitbucket_directory_url_keeps_include_paths() {
+        let input = crate::bitbucket::parser::parse_bitbucket_input(
+            "https://bitbucket.org/workspace/repo/src/main/docs/",
+            "bitbucket.org",
+        )
+        .unwrap();
+        let options = options(serde_json::json!({ "include_paths": ["crates/core"] }));
+        let (result, document) = export(
+            &scoped_source(),
+            &input,
+            &options,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        assert!(!result.repo.single_file);
+        assert_eq!(result.stats.files_processed, 2);
+        assert!(document.contains("// File: docs/guide.md"));
+        assert!(document.contains("// File: crates/core/lib.rs"));
+    }
+
+    #[tokio::test]
+    async fn symlinks_and_executables_are_marked_the_same_way_everywhere() {
+        let source = FakeSource::new(&[
+            ("bin/run.sh", Some("#!/bin/sh\n")),
+            ("run", Some("bin/run.sh")),
+        ])
+        .with_mode("bin/run.sh", "100755")
+        .with_mode("run", "120000");
+        let (result, document) = export(
+            &source,
+            &repo(None, false),
+            &options(serde_json::json!({})),
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.stats.files_processed, 2);
+        assert_eq!(result.stats.files_skipped, 0);
+        assert_eq!(result.stats.total_files, Some(2));
+
+        assert!(document.contains("run.sh*\n"));
+        assert!(document.contains("// File: bin/run.sh*\n"));
+        assert!(!document.contains("(executable)"));
+        assert!(document.contains("run -> bin/run.sh\n"));
+        assert!(document.contains("// File: run -> bin/run.sh\n"));
+    }
 }
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use url::Url;
+
+use crate::models::{RepoInfo, Submodule};
+
+/// `path → url` из `.gitmodules`; секции без одного из ключей пропускаются.
+pub fn parse_gitmodules(content: &str) -> HashMap<String, String> {
+    let mut urls = HashMap::new();
+    let mut path: Option<String> = None;
+    let mut url: Option<String> = None;
+
+    let mut flush = |path: &mut Option<String>, url: &mut Option<String>| {
+        if let (Some(path), Some(url)) = (path.take(), url.take()) {
+            urls.insert(path.trim_matches('/').to_string(), url);
+        }
+    };
+
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            flush(&mut path, &mut url);
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim().trim_matches('"').to_string();
+        match key.trim() {
+            "path" => path = Some(value),
+            "url" => url = Some(value),
+            _ => {}
+        }
+    }
+    flush(&mut path, &mut url);
+    urls
+}
+
+/// Относительный URL (`../lib.git`) отсчитывается от адреса самого репозитория.
+fn resolve_url(url: &str, repo_info: &RepoInfo) -> Option<String> {
+    if !url.starts_with("./") && !url.starts_with("../") {
+        return Some(url.to_string());
+    }
+    let host = repo_info.host.as_deref()?;
+    let base = Url::parse(&format!(
+        "https://{host}/{}/{}/",
+        repo_info.owner, repo_info.repo
+    ))
+    .ok()?;
+    base.join(url).ok().map(String::from)
+}
+
+/// Дополняет подмодули адресами из содержимого `.gitmodules`.
+pub fn attach_urls(submodules: &mut [Submodule], gitmodules: &str, repo_info: &RepoInfo) {
+    let urls = parse_gitmodules(gitmodules);
+    for submodule in submodules {
+        submodule.url = urls
+            .get(&submodule.path)
+            .and_then(|url| resolve_url(url, repo_info));
+    }
+}
+
+/// Строки раздела Repository Information: путь, закреплённый коммит и адрес.
+pub fn submodule_lines(submodules: &[Submodule]) -> String {
+    if submodules.is_empty() {
+        return String::new();
+    }
+    let mut lines = format!("- **Submodules:** {}\n", submodules.len());
+    for submodule in submodules {
+        let url = submodule.url.as_deref().unwrap_or("unknown URL");
+        lines.push_str(&format!(
+            "  - {} @ {} ({url})\n",
+            submodule.path, submodule.sha
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Forge;
+
+    fn repo_info(host: Option<&str>) -> RepoInfo {
+        RepoInfo {
+            owner: "acme".into(),
+            repo: "app".into(),
+            branch: None,
+            subdirectory: None,
+            original_url: "acme/app".into(),
+            commit_sha: None,
+            ref_kind: None,
+            ref_path: None,
+            single_file: false,
+            line_range: None,
+            forge: Forge::GitHub,
+            host: host.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn parses_sections_with_path_and_url() {
+        let gitmodules = r#"
+[submodule "vendor/lib"]
+	path = vendor/lib/
+	url = https://github.com/acme/lib.git
+[submodule "docs"]
+	path = "docs/theme"
+	url = ../theme.git
+[submodule "broken"]
+	url = https://example.com/broken.git
+"#;
+        let urls = parse_gitmodules(gitmodules);
+        assert_eq!(urls.len(), 2);
+        assert_eq!(urls["vendor/lib"], "https://github.com/acme/lib.git");
+        assert_eq!(urls["docs/theme"], "../theme.git");
+    }
+
+    #[test]
+    fn resolves_relative_urls_against_the_repository() {
+        let info = repo_info(Some("github.com"));
+        let cases = [
+            ("../theme.git", Some("https://github.com/acme/theme.git")),
+            ("./nested", Some("https://github.com/acme/app/nested")),
+            ("../../other/lib", Some("https://github.com/other/lib")),
+            ("git@github.com:x/y.git", Some("git@github.com:x/y.git")),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(resolve_url(url, &info).as_deref(), expected, "{url}");
+        }
+        assert_eq!(resolve_url("../theme.git", &repo_info(None)), None);
+    }
+
+    #[test]
+    fn attaches_urls_and_lists_submodules() {
+        let mut submodules = vec![
+            Submodule {
+                path: "vendor/lib".into(),
+                sha: "abcdef1234".into(),
+                url: None,
+            },
+            Submodule {
+                path: "missing".into(),
+                sha: "1234567".into(),
+                url: None,
+            },
+        ];
+        attach_urls(
+            &mut submodules,
+            "[submodule \"lib\"]\n\tpath = vendor/lib\n\turl = ../lib.git\n",
+            &repo_info(Some("github.com")),
+        );
+
+        assert_eq!(
+            submodule_lines(&submodules),
+            "- **Submodules:** 2\n\
+             \x20 - vendor/lib @ abcdef1234 (https://github.com/acme/lib.git)\n\
+             \x20 - missing @ 1234567 (unknown URL)\n"
+        );
+        assert_eq!(submodule_lines(&[]), "");
+    }
+}
@@ -1,6 +1,6 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 
-use crate::models::FileEntry;
+use crate::models::{FileEntry, Submodule};
 
 #[derive(Debug)]
 enum Node {
@@ -19,12 +19,35 @@ impl Node {
     }
 }
 
-pub fn generate_directory_tree(files: &[&FileEntry], subdirectory: Option<&str>) -> String {
+/// `submodules` are submodules not exported: they are shown as a leaf `name @ sha`.
+/// Symlinks from `links` are shown as `name -> target`, executables with `*` like `ls -F`.
+pub fn generate_directory_tree(
+    files: &[&FileEntry],
+    subdirectory: Option<&str>,
+    submodules: &[&Submodule],
+    links: &HashMap<String, String>,
+) -> String {
     let mut root = Node::Dir(BTreeMap::new());
     let prefix = subdirectory.unwrap_or("").trim_end_matches('/');
 
-    for file in files {
-        let mut path = file.path.as_str();
+    // (path, suffix of the leaf name): a symlink target may contain '/', so it is not
+    // appended to the path
+    let file_leaves = files.iter().map(|f| {
+        let suffix = match links.get(&f.path) {
+            Some(target) => format!(" -> {target}"),
+            None if f.is_executable() => "*".to_string(),
+            None => String::new(),
+        };
+        (f.path.as_str(), suffix)
+    });
+    let submodule_leaves = submodules.iter().map(|s| {
+        (
+            s.path.as_str(),
+            format!(" @ {}", &s.sha[..s.sha.len().min(7)]),
+        )
+    });
+
+    for (mut path, suffix) in file_leaves.chain(submodule_leaves) {
         if !prefix.is_empty() && path.starts_with(prefix) {
             path = path.trim_start_matches(prefix).trim_start_matches('/');
         }
@@ -35,7 +58,9 @@ pub fn generate_directory_tree(files: &[&FileEntry], subdirectory: Option<&str>)
             if is_last {
                 match current {
                     Node::Dir(children) => {
-                        children.entry(part.to_string()).or_insert(Node::File);
+                        children
+                            .entry(format!("{part}{suffix}"))
+                            .or_insert(Node::File);
                     }
                     Node::File => {}
                 }
@@ -122,3 +147,42 @@ pub fn generate_directory_tree(files: &[&FileEntry], subdirectory: Option<&str>)
 
     acc
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str) -> FileEntry {
+        FileEntry {
+            path: path.into(),
+            size: 1,
+            mode: Some("100644".into()),
+            sha: None,
+        }
+    }
+
+    #[test]
+    fn renders_directories_first_and_submodules_as_leaves() {
+        let files = [
+            file("README.md"),
+            file("src/main.rs"),
+            file("src/util/mod.rs"),
+        ];
+        let submodule = Submodule {
+            path: "vendor/lib".into(),
+            sha: "abcdef1234567890".into(),
+            url: None,
+        };
+        let tree = generate_directory_tree(
+            &files.iter().collect::<Vec<_>>(),
+            None,
+            &[&submodule],
+            &HashMap::new(),
+        );
+        let position = |leaf: &str| tree.find(leaf).unwrap_or_else(|| panic!("{leaf}:\n{tree}"));
+        assert!(position("── src\n") < position("── vendor\n"));
+        assert!(position("── vendor\n") < position("── README.md\n"));
+        assert!(position("── vendor\n") < position("── lib @ abcdef1\n"));
+        assert!(tree.contains("── mod.rs\n"));
+    }
+}
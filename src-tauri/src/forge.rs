@@ -0,0 +1,381 @@
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+use crate::bitbucket::client::BitbucketClient;
+use crate::bitbucket::parser::parse_bitbucket_input;
+use crate::gitea::client::GiteaClient;
+use crate::gitea::parser::parse_gitea_input;
+use crate::github::client::GitHubClient;
+use crate::github::parser::{parse_repository_input, same_host};
+use crate::gitlab::client::GitLabClient;
+use crate::gitlab::parser::parse_gitlab_input;
+use crate::http::client::ApiClient;
+use crate::lfs::{LfsPointer, download_object};
+use crate::models::{
+    DomainError, FetchStrategy, FileEntry, Forge, ForgeHost, HostProfile, Issue, RepoInfo,
+};
+use crate::source::{FileStream, IssueSource, Source, TreeListing};
+
+// Публичные хостинги, которые узнаются без записи в настройках
+const KNOWN_HOSTS: [(&str, Forge); 4] = [
+    ("github.com", Forge::GitHub),
+    ("gitlab.com", Forge::GitLab),
+    ("codeberg.org", Forge::Gitea),
+    ("bitbucket.org", Forge::Bitbucket),
+];
+// У Bitbucket Cloud API живёт на отдельном хосте
+const BITBUCKET_API_ROOT: &str = "https://api.bitbucket.org/2.0";
+
+/// Клиент хостинга, выбранный по `RepoInfo::forge`.
+pub enum ForgeClient {
+    GitHub(GitHubClient),
+    GitLab(GitLabClient),
+    Gitea(GiteaClient),
+    Bitbucket(BitbucketClient),
+}
+
+impl ForgeClient {
+    pub fn new(forge: Forge, api: ApiClient) -> Self {
+        match forge {
+            Forge::GitHub => Self::GitHub(GitHubClient::new(api)),
+            Forge::GitLab => Self::GitLab(GitLabClient::new(api)),
+            Forge::Gitea => Self::Gitea(GiteaClient::new(api)),
+            Forge::Bitbucket => Self::Bitbucket(BitbucketClient::new(api)),
+        }
+    }
+
+    fn api(&self) -> &ApiClient {
+        match self {
+            Self::GitHub(client) => client.api(),
+            Self::GitLab(client) => client.api(),
+            Self::Gitea(client) => client.api(),
+            Self::Bitbucket(client) => client.api(),
+        }
+    }
+}
+
+impl Source for ForgeClient {
+    async fn list_files(
+        &self,
+        input: &RepoInfo,
+        cancel: &CancellationToken,
+    ) -> Result<(RepoInfo, TreeListing), DomainError> {
+        match self {
+            Self::GitHub(client) => client.list_files(input, cancel).await,
+            Self::GitLab(client) => client.list_files(input, cancel).await,
+            Self::Gitea(client) => client.list_files(input, cancel).await,
+            Self::Bitbucket(client) => client.list_files(input, cancel).await,
+        }
+    }
+
+    async fn read_file(
+        &self,
+        repo: &RepoInfo,
+        path: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        match self {
+            Self::GitHub(client) => client.read_file(repo, path, cancel).await,
+            Self::GitLab(client) => client.read_file(repo, path, cancel).await,
+            Self::Gitea(client) => client.read_file(repo, path, cancel).await,
+            Self::Bitbucket(client) => client.read_file(repo, path, cancel).await,
+        }
+    }
+
+    async fn read_files(
+        &self,
+        repo: &RepoInfo,
+        paths: &[&str],
+        strategy: FetchStrategy,
+        cancel: &CancellationToken,
+    ) -> Result<Option<FileStream>, DomainError> {
+        match self {
+            Self::GitHub(client) => client.read_files(repo, paths, strategy, cancel).await,
+            Self::GitLab(client) => client.read_files(repo, paths, strategy, cancel).await,
+            Self::Gitea(client) => client.read_files(repo, paths, strategy, cancel).await,
+            Self::Bitbucket(client) => client.read_files(repo, paths, strategy, cancel).await,
+        }
+    }
+
+    async fn read_link_target(
+        &self,
+        repo: &RepoInfo,
+        file: &FileEntry,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        match self {
+            Self::GitHub(client) => client.read_link_target(repo, file, cancel).await,
+            Self::GitLab(client) => client.read_link_target(repo, file, cancel).await,
+            Self::Gitea(client) => client.read_link_target(repo, file, cancel).await,
+            Self::Bitbucket(client) => client.read_link_target(repo, file, cancel).await,
+        }
+    }
+
+    async fn read_lfs_object(
+        &self,
+        repo: &RepoInfo,
+        pointer: &LfsPointer,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        // Batch API у всех хостингов живёт по одному адресу рядом с git-репозиторием
+        let lfs_root = format!(
+            "https://{}/{}/{}.git/info/lfs",
+            self.api().host().web_host,
+            repo.owner,
+            repo.repo
+        );
+        download_object(self.api(), &lfs_root, pointer, cancel).await
+    }
+
+    // Подмодуль читается тем же клиентом, только если он на том же хосте: токен
+    // этого хоста другому не отправляется
+    fn submodule_input(&self, url: &str) -> Option<RepoInfo> {
+        let web_host = &self.api().host().web_host;
+        if !same_host(&input_host(url)?, web_host) {
+            return None;
+        }
+        match self {
+            Self::GitHub(_) => parse_repository_input(url, web_host),
+            Self::GitLab(_) => parse_gitlab_input(url, web_host),
+            Self::Gitea(_) => parse_gitea_input(url, web_host),
+            Self::Bitbucket(_) => parse_bitbucket_input(url, web_host),
+        }
+        .ok()
+    }
+
+    fn header_lines(&self, repo: &RepoInfo) -> String {
+        match self {
+            Self::GitHub(client) => client.header_lines(repo),
+            Self::GitLab(client) => client.header_lines(repo),
+            Self::Gitea(client) => client.header_lines(repo),
+            Self::Bitbucket(client) => client.header_lines(repo),
+        }
+    }
+}
+
+impl IssueSource for ForgeClient {
+    async fn fetch_issues(
+        &self,
+        repo: &RepoInfo,
+        cancel: &CancellationToken,
+    ) -> Result<(Vec<Issue>, bool), DomainError> {
+        match self {
+            Self::GitHub(client) => client.fetch_issues(repo, cancel).await,
+            Self::GitLab(client) => client.fetch_issues(repo, cancel).await,
+            Self::Gitea(client) => client.fetch_issues(repo, cancel).await,
+            Self::Bitbucket(client) => client.fetch_issues(repo, cancel).await,
+        }
+    }
+}
+
+/// Запись настроек для хоста; её токен и API используются только для этого хоста.
+pub fn find_forge_host<'a>(forge_hosts: &'a [ForgeHost], host: &str) -> Option<&'a ForgeHost> {
+    forge_hosts.iter().find(|h| same_host(&h.web_host, host))
+}
+
+/// Профиль API хоста, кроме настроенного хоста GitHub: `api_root` из настроек
+/// или стандартный путь хостинга.
+pub fn host_profile(forge: Forge, web_host: &str, configured: Option<&ForgeHost>) -> HostProfile {
+    let github = HostProfile::default();
+    if forge == Forge::GitHub && configured.is_none() && same_host(web_host, &github.web_host) {
+        return github;
+    }
+    let api_root = configured
+        .and_then(|h| h.api_root.clone())
+        .filter(|root| !root.trim().is_empty())
+        .unwrap_or_else(|| match forge {
+            Forge::GitHub => format!("https://{web_host}/api/v3"),
+            Forge::GitLab => format!("https://{web_host}/api/v4"),
+            Forge::Gitea => format!("https://{web_host}/api/v1"),
+            Forge::Bitbucket => BITBUCKET_API_ROOT.to_string(),
+        });
+    HostProfile {
+        web_host: web_host.to_string(),
+        api_root,
+        raw_root: None,
+    }
+}
+
+/// Разбирает ввод с учётом хоста: gitlab.com, codeberg.org, bitbucket.org и хосты из настроек уходят разборщику своего
+/// хостинга. github.com принимается всегда, даже если в настройках указан GHES; всё остальное
+/// (включая `owner/repo` без хоста) уходит разборщику GitHub с хостом `github_host`.
+pub fn parse_forge_input(
+    input: &str,
+    github_host: &str,
+    forge_hosts: &[ForgeHost],
+) -> Result<RepoInfo, DomainError> {
+    let detected = input_host(input).and_then(|host| {
+        let forge = find_forge_host(forge_hosts, &host)
+            .map(|h| h.forge)
+            .or_else(|| {
+                KNOWN_HOSTS
+                    .iter()
+                    .find(|(known, _)| same_host(known, &host))
+                    .map(|(_, forge)| *forge)
+            })?;
+        Some((forge, host))
+    });
+
+    match detected {
+        Some((Forge::GitLab, host)) => parse_gitlab_input(input, &host),
+        Some((Forge::Gitea, host)) => parse_gitea_input(input, &host),
+        Some((Forge::Bitbucket, host)) => parse_bitbucket_input(input, &host),
+        Some((Forge::GitHub, host)) => parse_repository_input(input, &host),
+        None => parse_repository_input(input, github_host),
+    }
+}
+
+/// Хост из URL, SSH-адреса (`git@host:group/project`) или записи `host.tld/group/project`;
+/// `None` для `owner/repo` — в именах пользователей GitHub точек не бывает.
+fn input_host(input: &str) -> Option<String> {
+    let input = input.trim();
+    if input.contains("://") {
+        return Url::parse(input)
+            .ok()?
+            .host_str()
+            .map(|h| h.to_ascii_lowercase());
+    }
+
+    if let Some((user_host, _)) = input.split_once(':')
+        && !user_host.contains('/')
+        && let Some((_, host)) = user_host.rsplit_once('@')
+    {
+        return Some(host.to_ascii_lowercase());
+    }
+
+    input
+        .split('/')
+        .next()
+        .filter(|first| first.contains('.'))
+        .map(str::to_ascii_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GITHUB: &str = "github.com";
+
+    fn forge_host(forge: Forge, web_host: &str) -> ForgeHost {
+        ForgeHost {
+            forge,
+            web_host: web_host.to_string(),
+            api_root: None,
+            token: None,
+        }
+    }
+
+    fn parse(input: &str, forge_hosts: &[ForgeHost]) -> RepoInfo {
+        parse_forge_input(input, GITHUB, forge_hosts)
+            .unwrap_or_else(|err| panic!("{input}: {err:?}"))
+    }
+
+    #[test]
+    fn input_host_from_urls_ssh_and_bare_hosts() {
+        let cases = [
+            ("https://GitLab.com/group/project", Some("gitlab.com")),
+            (
+                "http://gitlab.corp.example:8080/a/b",
+                Some("gitlab.corp.example"),
+            ),
+            ("git@gitlab.com:group/project.git", Some("gitlab.com")),
+            (
+                "ssh://git@gitlab.corp.example/group/project",
+                Some("gitlab.corp.example"),
+            ),
+            ("gitlab.com/group/project", Some("gitlab.com")),
+            ("  gitlab.com/group/project  ", Some("gitlab.com")),
+            ("owner/repo", None),
+            ("owner/repo@v1.0", None),
+            ("", None),
+        ];
+        for (input, host) in cases {
+            assert_eq!(input_host(input).as_deref(), host, "{input}");
+        }
+    }
+
+    #[test]
+    fn gitlab_hosts_are_detected() {
+        let info = parse("https://gitlab.com/group/sub/project", &[]);
+        assert_eq!(info.forge, Forge::GitLab);
+        assert_eq!(
+            (info.owner.as_str(), info.repo.as_str()),
+            ("group/sub", "project")
+        );
+
+        let hosts = [forge_host(Forge::GitLab, "gitlab.corp.example")];
+        let info = parse("git@gitlab.corp.example:team/app.git", &hosts);
+        assert_eq!(info.forge, Forge::GitLab);
+        assert_eq!(info.host.as_deref(), Some("gitlab.corp.example"));
+    }
+
+    #[test]
+    fn gitea_hosts_are_detected() {
+        let info = parse("https://codeberg.org/owner/repo", &[]);
+        assert_eq!(info.forge, Forge::Gitea);
+        assert_eq!((info.owner.as_str(), info.repo.as_str()), ("owner", "repo"));
+
+        let hosts = [forge_host(Forge::Gitea, "git.example.org")];
+        let info = parse("git.example.org/owner/repo", &hosts);
+        assert_eq!(info.forge, Forge::Gitea);
+        assert_eq!(info.host.as_deref(), Some("git.example.org"));
+    }
+
+    #[test]
+    fn bitbucket_is_detected() {
+        for input in [
+            "https://bitbucket.org/workspace/repo",
+            "git@bitbucket.org:workspace/repo.git",
+        ] {
+            let info = parse(input, &[]);
+            assert_eq!(info.forge, Forge::Bitbucket, "{input}");
+            assert_eq!(
+                (info.owner.as_str(), info.repo.as_str()),
+                ("workspace", "repo")
+            );
+        }
+    }
+
+    #[test]
+    fn other_inputs_go_to_github() {
+        for input in [
+            "owner/repo",
+            "https://github.com/owner/repo",
+            "git@github.com:owner/repo.git",
+        ] {
+            assert_eq!(parse(input, &[]).forge, Forge::GitHub, "{input}");
+        }
+        // Неизвестный хост разбирает GitHub-разборщик — и отклоняет
+        assert!(parse_forge_input("example.com/owner/repo", GITHUB, &[]).is_err());
+    }
+
+    #[test]
+    fn github_com_is_accepted_alongside_enterprise_host() {
+        let ghes = "git.corp.example";
+        for input in [
+            "https://github.com/owner/repo",
+            "git@github.com:owner/repo.git",
+        ] {
+            let info = parse_forge_input(input, ghes, &[]).unwrap();
+            assert_eq!(info.forge, Forge::GitHub, "{input}");
+            assert_eq!(info.host.as_deref(), Some("github.com"), "{input}");
+        }
+
+        let info = parse_forge_input("owner/repo", ghes, &[]).unwrap();
+        assert_eq!(info.host.as_deref(), Some(ghes));
+        let info = parse_forge_input("https://git.corp.example/owner/repo", ghes, &[]).unwrap();
+        assert_eq!(info.host.as_deref(), Some(ghes));
+    }
+
+    #[test]
+    fn github_profiles_follow_input_host() {
+        let profile = host_profile(Forge::GitHub, "github.com", None);
+        assert_eq!(profile.api_root, "https://api.github.com");
+
+        let hosts = [forge_host(Forge::GitHub, "git.other.example")];
+        let info = parse("https://git.other.example/owner/repo", &hosts);
+        assert_eq!(info.forge, Forge::GitHub);
+        let profile = host_profile(Forge::GitHub, "git.other.example", hosts.first());
+        assert_eq!(profile.api_root, "https://git.other.example/api/v3");
+    }
+}
@@ -0,0 +1,231 @@
+use serde::Deserialize;
+use tempfile::NamedTempFile;
+use tokio_util::sync::CancellationToken;
+
+use crate::http::client::{ARCHIVE_TIMEOUT, ApiClient, encode_path, ensure_not_cancelled};
+use crate::models::{DomainError, FileEntry, RefKind, Submodule};
+use crate::source::TreeListing;
+
+// Верхний предел страницы Git Trees API в настройках Gitea по умолчанию
+const TREE_PER_PAGE: u32 = 1_000;
+const MAX_TREE_PAGES: u32 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct RepoMetadata {
+    pub default_branch: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TreeItem {
+    path: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    size: u64,
+    sha: String,
+    #[serde(default)]
+    mode: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TreeResponse {
+    #[serde(default)]
+    tree: Vec<TreeItem>,
+    /// Есть следующие страницы
+    #[serde(default)]
+    truncated: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitResponse {
+    sha: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BranchCommit {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BranchResponse {
+    commit: BranchCommit,
+}
+
+#[derive(Debug, Deserialize)]
+struct TagResponse {
+    commit: CommitResponse,
+}
+
+/// Клиент API `/api/v1` Gitea и Forgejo (Codeberg): по устройству он почти повторяет
+/// GitHub REST API, но дерево отдаёт постранично, а не обрезает.
+#[derive(Clone)]
+pub struct GiteaClient {
+    api: ApiClient,
+}
+
+impl GiteaClient {
+    pub fn new(api: ApiClient) -> Self {
+        Self { api }
+    }
+
+    pub fn api(&self) -> &ApiClient {
+        &self.api
+    }
+
+    pub async fn get_repo_metadata(
+        &self,
+        owner: &str,
+        repo: &str,
+        cancel: &CancellationToken,
+    ) -> Result<RepoMetadata, DomainError> {
+        let url = self.api.api_url(&format!("repos/{owner}/{repo}"));
+        self.api
+            .get_json(&url, "Gitea Repository API", cancel)
+            .await
+    }
+
+    /// Коммит, на который указывает ветка, тег или (в том числе сокращённый) SHA;
+    /// `None` — такого ref нет.
+    pub async fn ref_target(
+        &self,
+        owner: &str,
+        repo: &str,
+        kind: RefKind,
+        name: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        let collection = match kind {
+            RefKind::Branch => "branches",
+            RefKind::Tag => "tags",
+            RefKind::Commit => "git/commits",
+        };
+        let url = self.api.api_url(&format!(
+            "repos/{owner}/{repo}/{collection}/{}",
+            encode_path(name)
+        ));
+        let context = "Gitea Repository API";
+        // Коммит ветки лежит в `commit.id`, тега — в `commit.sha`
+        let result = match kind {
+            RefKind::Branch => self
+                .api
+                .get_json::<BranchResponse>(&url, context, cancel)
+                .await
+                .map(|b| b.commit.id),
+            RefKind::Tag => self
+                .api
+                .get_json::<TagResponse>(&url, context, cancel)
+                .await
+                .map(|t| t.commit.sha),
+            RefKind::Commit => self
+                .api
+                .get_json::<CommitResponse>(&url, context, cancel)
+                .await
+                .map(|c| c.sha),
+        };
+        match result {
+            Ok(sha) => Ok(Some(sha)),
+            Err(DomainError::NotFound) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Полный список файлов коммита: рекурсивное дерево читается по страницам.
+    pub async fn get_tree(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+        cancel: &CancellationToken,
+    ) -> Result<TreeListing, DomainError> {
+        let mut files = Vec::new();
+        let mut submodules = Vec::new();
+        let mut truncated = false;
+
+        for page in 1..=MAX_TREE_PAGES {
+            ensure_not_cancelled(cancel)?;
+
+            let url = self.api.api_url(&format!(
+                "repos/{owner}/{repo}/git/trees/{sha}?recursive=true&per_page={TREE_PER_PAGE}&page={page}"
+            ));
+            let data: TreeResponse = self
+                .api
+                .get_json(&url, "Gitea Git Trees API", cancel)
+                .await?;
+            let more = data.truncated && !data.tree.is_empty();
+
+            for item in data.tree {
+                match item.kind.as_str() {
+                    "blob" => files.push(FileEntry {
+                        path: item.path,
+                        size: item.size,
+                        mode: item.mode,
+                        sha: Some(item.sha),
+                    }),
+                    // подмодуль: sha — закреплённый коммит его репозитория
+                    "commit" => submodules.push(Submodule {
+                        path: item.path,
+                        sha: item.sha,
+                        url: None,
+                    }),
+                    _ => {}
+                }
+            }
+
+            if !more {
+                break;
+            }
+            if page == MAX_TREE_PAGES {
+                truncated = true;
+            }
+        }
+
+        if files.is_empty() {
+            return Err(DomainError::NotFound);
+        }
+
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(TreeListing {
+            files,
+            truncated,
+            submodules,
+            unreadable: 0,
+        })
+    }
+
+    pub async fn get_raw_content(
+        &self,
+        owner: &str,
+        repo: &str,
+        reference: &str,
+        path: &str,
+        cancel: &CancellationToken,
+    ) -> Result<String, DomainError> {
+        ensure_not_cancelled(cancel)?;
+
+        let url = self.api.api_url(&format!(
+            "repos/{owner}/{repo}/raw/{}?ref={}",
+            encode_path(path),
+            encode_path(reference)
+        ));
+        self.api
+            .get_text(self.api.get(&url), "Gitea raw content", cancel)
+            .await
+    }
+
+    /// Скачивает tar.gz-архив репозитория на указанном ref; внутри всё лежит в `repo/`.
+    pub async fn get_archive(
+        &self,
+        owner: &str,
+        repo: &str,
+        reference: &str,
+        cancel: &CancellationToken,
+    ) -> Result<NamedTempFile, DomainError> {
+        let url = self.api.api_url(&format!(
+            "repos/{owner}/{repo}/archive/{}.tar.gz",
+            encode_path(reference)
+        ));
+        self.api
+            .download(self.api.get(&url), "Gitea archive", cancel, ARCHIVE_TIMEOUT)
+            .await
+    }
+}
@@ -0,0 +1,50 @@
+use tokio_util::sync::CancellationToken;
+
+use crate::gitea::client::GiteaClient;
+use crate::github::issues::{IssueResponse, to_issue};
+use crate::models::{DomainError, Issue};
+
+pub async fn fetch_issues(
+    client: &GiteaClient,
+    owner: &str,
+    repo: &str,
+    cancel: &CancellationToken,
+) -> Result<(Vec<Issue>, bool), DomainError> {
+    // Больше 50 записей на страницу Gitea по умолчанию не отдаёт
+    const PER_PAGE: u32 = 50;
+    const MAX_PAGES: u32 = 20; // до 1000 issues
+    let mut all = Vec::new();
+    let mut truncated = false;
+
+    for page in 1..=MAX_PAGES {
+        if cancel.is_cancelled() {
+            return Err(DomainError::Cancelled);
+        }
+
+        // type=issues отсекает pull requests на стороне сервера
+        let url = client.api().api_url(&format!(
+            "repos/{owner}/{repo}/issues?state=all&type=issues&limit={PER_PAGE}&page={page}"
+        ));
+
+        let page_items: Vec<IssueResponse> = client
+            .api()
+            .get_json(&url, "Gitea Issues API", cancel)
+            .await?;
+
+        if page_items.is_empty() {
+            break;
+        }
+
+        all.extend(page_items.iter().filter_map(to_issue));
+
+        if page_items.len() < PER_PAGE as usize {
+            break;
+        }
+
+        if page == MAX_PAGES {
+            truncated = true;
+        }
+    }
+
+    Ok((all, truncated))
+}
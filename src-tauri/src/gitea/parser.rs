@@ -0,0 +1,93 @@
+use url::Url;
+
+use crate::github::parser::{invalid, parse_line_range, remote_to_web_url, split_ref_suffix};
+use crate::models::{DomainError, Forge, RefKind, RepoInfo};
+
+fn ref_kind_from(segment: &str) -> Option<RefKind> {
+    match segment {
+        "branch" => Some(RefKind::Branch),
+        "tag" => Some(RefKind::Tag),
+        "commit" => Some(RefKind::Commit),
+        _ => None,
+    }
+}
+
+/// Адрес репозитория Gitea или Forgejo: веб-URL (`https://codeberg.org/owner/repo/src/branch/main/docs`),
+/// SSH-адрес или `host/owner/repo`. В отличие от GitHub, тип ref записан прямо в пути
+/// (`src/branch/...`, `src/tag/...`, `src/commit/...`).
+pub fn parse_gitea_input(input: &str, web_host: &str) -> Result<RepoInfo, DomainError> {
+    let normalized = input.trim();
+    let candidate = match remote_to_web_url(normalized, web_host)? {
+        Some(url) => url,
+        None if normalized.starts_with("http://") || normalized.starts_with("https://") => {
+            normalized.to_string()
+        }
+        None => format!("https://{normalized}"),
+    };
+
+    let url =
+        Url::parse(&candidate).map_err(|_| invalid(normalized, "the URL could not be parsed"))?;
+    let segments: Vec<String> = url
+        .path_segments()
+        .map(|s| s.filter(|s| !s.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default();
+
+    let [owner, name, rest @ ..] = segments.as_slice() else {
+        return Err(invalid(
+            normalized,
+            "the URL must include the owner and repository, e.g. /owner/repo",
+        ));
+    };
+
+    let (repo, mut branch) = split_ref_suffix(name);
+    let repo = repo.trim_end_matches(".git").to_string();
+
+    let mut subdirectory: Option<String> = None;
+    let mut ref_kind: Option<RefKind> = None;
+    let mut ref_path: Option<String> = None;
+    let mut single_file = false;
+
+    match rest {
+        [view, kind, reference, path @ ..]
+            if (view == "src" || view == "raw") && ref_kind_from(kind).is_some() =>
+        {
+            single_file = !path.is_empty();
+            ref_kind = ref_kind_from(kind);
+            branch = Some(reference.clone());
+            if !path.is_empty() {
+                subdirectory = Some(path.join("/"));
+                // имя коммита '/' не содержит, а ветки и теги делит резолвер
+                if ref_kind != Some(RefKind::Commit) {
+                    ref_path = Some(rest[2..].join("/"));
+                }
+            }
+        }
+        [view, sha, ..] if view == "commit" => {
+            branch = Some(sha.clone());
+            ref_kind = Some(RefKind::Commit);
+        }
+        [view, kind, tag @ ..] if view == "releases" && kind == "tag" && !tag.is_empty() => {
+            branch = Some(tag.join("/"));
+            ref_kind = Some(RefKind::Tag);
+        }
+        _ => {}
+    }
+
+    Ok(RepoInfo {
+        owner: owner.clone(),
+        repo,
+        branch,
+        subdirectory,
+        original_url: normalized.to_string(),
+        commit_sha: None,
+        ref_kind,
+        ref_path,
+        single_file,
+        line_range: url
+            .fragment()
+            .filter(|_| single_file)
+            .and_then(parse_line_range),
+        forge: Forge::Gitea,
+        host: Some(web_host.trim_end_matches('/').to_string()),
+    })
+}
@@ -0,0 +1,92 @@
+use tokio_util::sync::CancellationToken;
+
+use crate::gitea::client::GiteaClient;
+use crate::gitea::issues::fetch_issues;
+use crate::models::{DomainError, FetchStrategy, Issue, RefKind, RepoInfo};
+use crate::refs::{RefLookup, resolve_repository_files};
+use crate::source::{
+    FileStream, IssueSource, Source, TreeListing, choose_fetch_strategy, content_ref,
+    extract_tarball,
+};
+
+impl Source for GiteaClient {
+    async fn list_files(
+        &self,
+        input: &RepoInfo,
+        cancel: &CancellationToken,
+    ) -> Result<(RepoInfo, TreeListing), DomainError> {
+        resolve_repository_files(self, input, cancel).await
+    }
+
+    async fn read_file(
+        &self,
+        repo: &RepoInfo,
+        path: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        self.get_raw_content(&repo.owner, &repo.repo, content_ref(repo), path, cancel)
+            .await
+            .map(Some)
+    }
+
+    async fn read_files(
+        &self,
+        repo: &RepoInfo,
+        paths: &[&str],
+        strategy: FetchStrategy,
+        cancel: &CancellationToken,
+    ) -> Result<Option<FileStream>, DomainError> {
+        match choose_fetch_strategy(strategy, paths.len()) {
+            FetchStrategy::Archive => {
+                let archive = self
+                    .get_archive(&repo.owner, &repo.repo, content_ref(repo), cancel)
+                    .await?;
+                Ok(Some(extract_tarball(archive, paths, cancel)))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+impl IssueSource for GiteaClient {
+    async fn fetch_issues(
+        &self,
+        repo: &RepoInfo,
+        cancel: &CancellationToken,
+    ) -> Result<(Vec<Issue>, bool), DomainError> {
+        fetch_issues(self, &repo.owner, &repo.repo, cancel).await
+    }
+}
+
+impl RefLookup for GiteaClient {
+    async fn ref_target(
+        &self,
+        owner: &str,
+        repo: &str,
+        kind: RefKind,
+        name: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        GiteaClient::ref_target(self, owner, repo, kind, name, cancel).await
+    }
+
+    async fn default_branch(
+        &self,
+        owner: &str,
+        repo: &str,
+        cancel: &CancellationToken,
+    ) -> Result<String, DomainError> {
+        let metadata = self.get_repo_metadata(owner, repo, cancel).await?;
+        Ok(metadata.default_branch)
+    }
+
+    async fn get_tree(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+        cancel: &CancellationToken,
+    ) -> Result<TreeListing, DomainError> {
+        GiteaClient::get_tree(self, owner, repo, sha, cancel).await
+    }
+}
@@ -1,109 +1,325 @@
-use std::sync::Arc;
+use std::collections::HashMap;
 
-use reqwest::{Client, header};
+use futures::{StreamExt, stream};
+use reqwest::header;
+use serde::Deserialize;
+use tempfile::NamedTempFile;
 use tokio_util::sync::CancellationToken;
 
-use crate::models::{DomainError, FileEntry};
+use crate::http::client::{
+    ARCHIVE_TIMEOUT, ApiClient, encode_component, encode_path, ensure_not_cancelled,
+};
+use crate::models::{DomainError, FileEntry, Submodule};
+use crate::source::TreeListing;
 
-const USER_AGENT: &str = "mergist";
+const RAW_MEDIA_TYPE: &str = "application/vnd.github.raw";
+const SHA_MEDIA_TYPE: &str = "application/vnd.github.sha";
+// Walking a truncated tree: request budget and concurrency
+const MAX_TREE_WALK_REQUESTS: usize = 2_000;
+const TREE_WALK_CONCURRENCY: usize = 8;
+
+#[derive(Debug, Deserialize)]
+pub struct RepoMetadata {
+    pub default_branch: String,
+}
+
+struct TreePage {
+    files: Vec<FileEntry>,
+    /// Nested directories, for the non-recursive walk
+    subtrees: Vec<Subtree>,
+    submodules: Vec<Submodule>,
+    truncated: bool,
+}
+
+struct Subtree {
+    path: String,
+    sha: String,
+    /// Files and submodules of the directory listed before it: the subtree's contents
+    /// are inserted at this point, as in the recursive response
+    files_before: usize,
+    submodules_before: usize,
+}
 
 #[derive(Clone)]
 pub struct GitHubClient {
-    client: Arc<Client>,
+    api: ApiClient,
 }
 
 impl GitHubClient {
-    pub fn new() -> Result<Self, DomainError> {
-        let mut headers = header::HeaderMap::new();
-        headers.insert(
-            header::USER_AGENT,
-            header::HeaderValue::from_static(USER_AGENT),
-        );
-
-        let client = Client::builder()
-            .default_headers(headers)
-            .build()
-            .map_err(|e| DomainError::Unexpected(e.to_string()))?;
-
-        Ok(Self {
-            client: Arc::new(client),
-        })
+    pub fn new(api: ApiClient) -> Self {
+        Self { api }
     }
 
-    pub fn client(&self) -> &Client {
-        self.client.as_ref()
+    pub fn api(&self) -> &ApiClient {
+        &self.api
     }
 
-    pub async fn get_tree(
+    /// Downloads the tar.gz archive of the repository at `reference` into a temp file
+    /// (reqwest follows the codeload redirect; the signed link carries private access).
+    pub async fn get_tarball(
         &self,
         owner: &str,
         repo: &str,
-        branch: &str,
+        reference: &str,
         cancel: &CancellationToken,
-    ) -> Result<Vec<FileEntry>, DomainError> {
-        ensure_not_cancelled(cancel)?;
+    ) -> Result<NamedTempFile, DomainError> {
+        let url = self
+            .api
+            .api_url(&format!("repos/{owner}/{repo}/tarball/{reference}"));
+        self.api
+            .download(
+                self.api.get(&url),
+                "GitHub tarball",
+                cancel,
+                ARCHIVE_TIMEOUT,
+            )
+            .await
+    }
 
-        let url =
-            format!("https://api.github.com/repos/{owner}/{repo}/git/trees/{branch}?recursive=1");
+    pub async fn get_repo_metadata(
+        &self,
+        owner: &str,
+        repo: &str,
+        cancel: &CancellationToken,
+    ) -> Result<RepoMetadata, DomainError> {
+        let url = self.api.api_url(&format!("repos/{owner}/{repo}"));
+        self.api.get_json(&url, "GitHub Repos API", cancel).await
+    }
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
+    /// Checks that a ref such as `heads/main` or `tags/v1.4.2` exists.
+    pub async fn ref_exists(
+        &self,
+        owner: &str,
+        repo: &str,
+        qualified_ref: &str,
+        cancel: &CancellationToken,
+    ) -> Result<bool, DomainError> {
+        let url = self.api.api_url(&format!(
+            "repos/{owner}/{repo}/git/ref/{}",
+            encode_path(qualified_ref)
+        ));
+        match self
+            .api
+            .get_json::<serde_json::Value>(&url, "GitHub Git Refs API", cancel)
             .await
-            .map_err(|e| DomainError::Network(e.to_string()))?;
-
-        if response.status() == reqwest::StatusCode::NOT_FOUND {
-            return Err(DomainError::NotFound);
+        {
+            Ok(_) => Ok(true),
+            Err(DomainError::NotFound) => Ok(false),
+            Err(err) => Err(err),
         }
+    }
 
-        if !response.status().is_success() {
-            return Err(DomainError::Network(format!(
-                "GitHub Trees API returned {}",
-                response.status()
-            )));
+    /// Names of refs with the given prefix (`heads/feature` → `feature`, `feature/auth`, ...).
+    pub async fn matching_refs(
+        &self,
+        owner: &str,
+        repo: &str,
+        prefix: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<String>, DomainError> {
+        #[derive(Deserialize)]
+        struct MatchingRef {
+            #[serde(rename = "ref")]
+            name: String,
         }
 
-        let data: serde_json::Value = response
-            .json()
+        let url = self.api.api_url(&format!(
+            "repos/{owner}/{repo}/git/matching-refs/{}",
+            encode_path(prefix)
+        ));
+        let refs: Vec<MatchingRef> = self
+            .api
+            .get_json(&url, "GitHub Git Refs API", cancel)
+            .await?;
+        Ok(refs
+            .into_iter()
+            .filter_map(|r| {
+                r.name
+                    .strip_prefix("refs/heads/")
+                    .or_else(|| r.name.strip_prefix("refs/tags/"))
+                    .map(str::to_string)
+            })
+            .collect())
+    }
+
+    /// Checks whether the file or directory `path` exists in the tree of `reference`.
+    pub async fn path_exists(
+        &self,
+        owner: &str,
+        repo: &str,
+        reference: &str,
+        path: &str,
+        cancel: &CancellationToken,
+    ) -> Result<bool, DomainError> {
+        let url = self.api.api_url(&format!(
+            "repos/{owner}/{repo}/contents/{}?ref={}",
+            encode_path(path),
+            encode_component(reference)
+        ));
+        match self
+            .api
+            .get_json::<serde_json::Value>(&url, "GitHub Contents API", cancel)
             .await
-            .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        {
+            Ok(_) => Ok(true),
+            Err(DomainError::NotFound) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
 
-        let tree_items = data
-            .get("tree")
-            .and_then(|t| t.as_array())
-            .ok_or_else(|| DomainError::Unexpected("Malformed GitHub Trees response".into()))?;
+    /// SHA of the commit a ref points to: `refs/heads/…`, `refs/tags/…` or an abbreviated SHA.
+    pub async fn resolve_commit_sha(
+        &self,
+        owner: &str,
+        repo: &str,
+        reference: &str,
+        cancel: &CancellationToken,
+    ) -> Result<String, DomainError> {
+        let url = self.api.api_url(&format!(
+            "repos/{owner}/{repo}/commits/{}",
+            encode_path(reference)
+        ));
+        let request = self.api.get(&url).header(header::ACCEPT, SHA_MEDIA_TYPE);
+        let sha = self
+            .api
+            .get_text(request, "GitHub Commits API", cancel)
+            .await?;
+        Ok(sha.trim().to_string())
+    }
 
-        let mut files = Vec::new();
-        for item in tree_items {
-            ensure_not_cancelled(cancel)?;
+    /// Full file listing of a ref. When the recursive response is truncated (GitHub returns
+    /// at most 100k entries / 7MB), the tree is walked by subtree SHA without recursion.
+    pub async fn get_tree(
+        &self,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+        cancel: &CancellationToken,
+    ) -> Result<TreeListing, DomainError> {
+        ensure_not_cancelled(cancel)?;
+
+        let url = self.api.api_url(&format!(
+            "repos/{owner}/{repo}/git/trees/{}?recursive=1",
+            encode_path(branch)
+        ));
 
-            let is_blob = item
-                .get("type")
-                .and_then(|t| t.as_str())
-                .map(|t| t == "blob")
-                .unwrap_or(false);
+        let data: serde_json::Value = self.api.get_json(&url, "GitHub Trees API", cancel).await?;
+        let page = parse_tree_page(&data, "")?;
 
-            if !is_blob {
-                continue;
+        let listing = if page.truncated {
+            let walked = self.walk_tree(owner, repo, branch, cancel).await?;
+            // The walk stopped before it collected more than the truncated recursive response
+            if walked.truncated && walked.files.len() < page.files.len() {
+                TreeListing {
+                    files: page.files,
+                    truncated: true,
+                    submodules: page.submodules,
+                    unreadable: 0,
+                }
+            } else {
+                walked
             }
+        } else {
+            TreeListing {
+                files: page.files,
+                truncated: false,
+                submodules: page.submodules,
+                unreadable: 0,
+            }
+        };
 
-            let path = item
-                .get("path")
-                .and_then(|p| p.as_str())
-                .unwrap_or_default()
-                .to_string();
+        if listing.files.is_empty() {
+            return Err(DomainError::NotFound);
+        }
 
-            let size = item.get("size").and_then(|s| s.as_u64()).unwrap_or(0);
+        Ok(listing)
+    }
 
-            files.push(FileEntry { path, size });
-        }
+    /// Walks the tree directory by directory when the recursive response is truncated.
+    /// An interrupted walk (request budget, network failure) returns what it has with
+    /// `truncated` set; files keep the order of git's recursive response.
+    async fn walk_tree(
+        &self,
+        owner: &str,
+        repo: &str,
+        root: &str,
+        cancel: &CancellationToken,
+    ) -> Result<TreeListing, DomainError> {
+        let mut pages = HashMap::new();
+        let mut truncated = false;
+        let mut requests = 0usize;
+        // (directory path, sha/ref of its tree); walked level by level, each level in parallel
+        let mut level = vec![(String::new(), root.to_string())];
 
-        if files.is_empty() {
-            return Err(DomainError::NotFound);
+        while !level.is_empty() {
+            if requests + level.len() > MAX_TREE_WALK_REQUESTS {
+                level.truncate(MAX_TREE_WALK_REQUESTS - requests);
+                truncated = true;
+            }
+            requests += level.len();
+
+            let results: Vec<(String, Result<TreePage, DomainError>)> =
+                // owned items: a future borrowing the element fails the Send check
+                stream::iter(level.drain(..).map(|(prefix, sha)| async move {
+                    let url = self.api.api_url(&format!("repos/{owner}/{repo}/git/trees/{sha}"));
+                    let page = match self.api.get_json(&url, "GitHub Trees API", cancel).await {
+                        Ok(data) => parse_tree_page(&data, &prefix),
+                        Err(err) => Err(err),
+                    };
+                    (prefix, page)
+                }))
+                .buffer_unordered(TREE_WALK_CONCURRENCY)
+                .collect()
+                .await;
+
+            let mut failed = false;
+            for (prefix, page) in results {
+                match page {
+                    Ok(page) => {
+                        truncated |= page.truncated;
+                        level.extend(
+                            page.subtrees
+                                .iter()
+                                .map(|s| (s.path.clone(), s.sha.clone())),
+                        );
+                        pages.insert(prefix, page);
+                    }
+                    Err(DomainError::Cancelled) => return Err(DomainError::Cancelled),
+                    Err(_) => failed = true,
+                }
+            }
+            if failed {
+                truncated = true;
+                break;
+            }
         }
 
-        Ok(files)
+        let mut files = Vec::new();
+        let mut submodules = Vec::new();
+        collect_tree(&mut pages, "", &mut files, &mut submodules);
+        Ok(TreeListing {
+            files,
+            truncated,
+            submodules,
+            unreadable: 0,
+        })
+    }
+
+    /// Blob content by SHA; for a symlink this is the path it points to.
+    pub async fn get_blob(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+        cancel: &CancellationToken,
+    ) -> Result<String, DomainError> {
+        let url = self
+            .api
+            .api_url(&format!("repos/{owner}/{repo}/git/blobs/{sha}"));
+        let request = self.api.get(&url).header(header::ACCEPT, RAW_MEDIA_TYPE);
+        self.api
+            .get_text(request, "GitHub Git Blobs API", cancel)
+            .await
     }
 
     pub async fn get_raw_content(
@@ -116,39 +332,167 @@ impl GitHubClient {
     ) -> Result<String, DomainError> {
         ensure_not_cancelled(cancel)?;
 
-        let url = format!("https://raw.githubusercontent.com/{owner}/{repo}/{branch}/{path}");
+        // The raw host does not serve private files (and GHES may have none at all),
+        // so in those cases read through the Contents API in raw representation
+        let request = match &self.api.host().raw_root {
+            Some(raw_root) if !self.api.has_token() => {
+                let url = format!(
+                    "{raw_root}/{owner}/{repo}/{}/{}",
+                    encode_path(branch),
+                    encode_path(path)
+                );
+                self.api.get(&url)
+            }
+            _ => {
+                let url = self.api.api_url(&format!(
+                    "repos/{owner}/{repo}/contents/{}?ref={}",
+                    encode_path(path),
+                    encode_component(branch)
+                ));
+                self.api.get(&url).header(header::ACCEPT, RAW_MEDIA_TYPE)
+            }
+        };
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| DomainError::Network(e.to_string()))?;
+        self.api.get_text(request, "Raw content", cancel).await
+    }
+}
 
-        ensure_not_cancelled(cancel)?;
+// Assembles walked pages in the order of the recursive response: a directory's contents
+// follow the entries that precede it in the parent
+fn collect_tree(
+    pages: &mut HashMap<String, TreePage>,
+    prefix: &str,
+    files: &mut Vec<FileEntry>,
+    submodules: &mut Vec<Submodule>,
+) {
+    // A directory the walk did not reach
+    let Some(page) = pages.remove(prefix) else {
+        return;
+    };
+    let mut page_files = page.files.into_iter();
+    let mut page_submodules = page.submodules.into_iter();
+    let (mut files_taken, mut submodules_taken) = (0, 0);
+    for subtree in page.subtrees {
+        files.extend(page_files.by_ref().take(subtree.files_before - files_taken));
+        submodules.extend(
+            page_submodules
+                .by_ref()
+                .take(subtree.submodules_before - submodules_taken),
+        );
+        files_taken = subtree.files_before;
+        submodules_taken = subtree.submodules_before;
+        collect_tree(pages, &subtree.path, files, submodules);
+    }
+    files.extend(page_files);
+    submodules.extend(page_submodules);
+}
 
-        if response.status() == reqwest::StatusCode::NOT_FOUND {
-            return Err(DomainError::NotFound);
-        }
+fn parse_tree_page(data: &serde_json::Value, prefix: &str) -> Result<TreePage, DomainError> {
+    let tree_items = data
+        .get("tree")
+        .and_then(|t| t.as_array())
+        .ok_or_else(|| DomainError::Unexpected("Malformed GitHub Trees response".into()))?;
 
-        if !response.status().is_success() {
-            return Err(DomainError::Network(format!(
-                "Raw content returned {}",
-                response.status()
-            )));
-        }
+    let mut page = TreePage {
+        files: Vec::new(),
+        subtrees: Vec::new(),
+        submodules: Vec::new(),
+        truncated: data
+            .get("truncated")
+            .and_then(|t| t.as_bool())
+            .unwrap_or(false),
+    };
 
-        response
-            .text()
-            .await
-            .map_err(|e| DomainError::Unexpected(e.to_string()))
+    for item in tree_items {
+        let field = |name: &str| item.get(name).and_then(|v| v.as_str()).unwrap_or_default();
+
+        let path = if prefix.is_empty() {
+            field("path").to_string()
+        } else {
+            format!("{prefix}/{}", field("path"))
+        };
+
+        match field("type") {
+            "blob" => {
+                let size = item.get("size").and_then(|s| s.as_u64()).unwrap_or(0);
+                page.files.push(FileEntry {
+                    path,
+                    size,
+                    mode: Some(field("mode").to_string()),
+                    sha: Some(field("sha").to_string()),
+                });
+            }
+            "tree" => page.subtrees.push(Subtree {
+                path,
+                sha: field("sha").to_string(),
+                files_before: page.files.len(),
+                submodules_before: page.submodules.len(),
+            }),
+            // submodule: sha is the pinned commit of its repository
+            "commit" => page.submodules.push(Submodule {
+                path,
+                sha: field("sha").to_string(),
+                url: None,
+            }),
+            _ => {}
+        }
     }
+
+    Ok(page)
 }
 
-fn ensure_not_cancelled(cancel: &CancellationToken) -> Result<(), DomainError> {
-    if cancel.is_cancelled() {
-        Err(DomainError::Cancelled)
-    } else {
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn page(prefix: &str, entries: &[(&str, &str)]) -> TreePage {
+        let tree: Vec<_> = entries
+            .iter()
+            .map(|(path, kind)| json!({ "path": path, "type": kind, "sha": format!("{path}-sha") }))
+            .collect();
+        parse_tree_page(&json!({ "tree": tree }), prefix).unwrap()
+    }
+
+    #[test]
+    fn walked_pages_keep_recursive_tree_order() {
+        let mut pages = HashMap::from([
+            (
+                String::new(),
+                page(
+                    "",
+                    &[
+                        ("a.txt", "blob"),
+                        ("b", "tree"),
+                        ("c.txt", "blob"),
+                        ("d", "tree"),
+                        ("lib", "commit"),
+                        ("z.txt", "blob"),
+                    ],
+                ),
+            ),
+            (
+                "b".to_string(),
+                page(
+                    "b",
+                    &[("inner", "tree"), ("x.txt", "blob"), ("vendor", "commit")],
+                ),
+            ),
+            ("b/inner".to_string(), page("b/inner", &[("y.txt", "blob")])),
+        ]);
+
+        let mut files = Vec::new();
+        let mut submodules = Vec::new();
+        collect_tree(&mut pages, "", &mut files, &mut submodules);
+
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        // The walk never fetched `d`, so its files are simply missing
+        assert_eq!(
+            paths,
+            ["a.txt", "b/inner/y.txt", "b/x.txt", "c.txt", "z.txt"]
+        );
+        let submodules: Vec<&str> = submodules.iter().map(|s| s.path.as_str()).collect();
+        assert_eq!(submodules, ["b/vendor", "lib"]);
     }
 }
@@ -19,8 +19,9 @@ struct IssueMilestone {
     title: Option<String>,
 }
 
+/// Формат issue GitHub; Gitea и Forgejo отдают тот же.
 #[derive(Debug, Deserialize)]
-struct IssueResponse {
+pub struct IssueResponse {
     number: u64,
     title: String,
     state: String,
@@ -53,32 +54,14 @@ pub async fn fetch_issues(
             return Err(DomainError::Cancelled);
         }
 
-        let url = format!(
-            "https://api.github.com/repos/{owner}/{repo}/issues?state=all&per_page={PER_PAGE}&page={page}"
-        );
+        let url = client.api().api_url(&format!(
+            "repos/{owner}/{repo}/issues?state=all&per_page={PER_PAGE}&page={page}"
+        ));
 
-        let resp = client
-            .client()
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| DomainError::Network(e.to_string()))?;
-
-        if resp.status() == reqwest::StatusCode::NOT_FOUND {
-            return Err(DomainError::NotFound);
-        }
-
-        if !resp.status().is_success() {
-            return Err(DomainError::Network(format!(
-                "GitHub Issues API returned {}",
-                resp.status()
-            )));
-        }
-
-        let page_items: Vec<IssueResponse> = resp
-            .json()
-            .await
-            .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+        let page_items: Vec<IssueResponse> = client
+            .api()
+            .get_json(&url, "GitHub Issues API", cancel)
+            .await?;
 
         if page_items.is_empty() {
             break;
@@ -116,7 +99,7 @@ fn assignees_from(item: &IssueResponse) -> Vec<String> {
         .collect()
 }
 
-fn to_issue(item: &IssueResponse) -> Option<Issue> {
+pub fn to_issue(item: &IssueResponse) -> Option<Issue> {
     // пропускаем PR
     if item.pull_request.is_some() {
         return None;
@@ -1,3 +1,5 @@
 pub mod client;
 pub mod issues;
 pub mod parser;
+pub mod refs;
+pub mod source;
@@ -1,31 +1,168 @@
-use crate::models::{DomainError, RepoInfo};
+use crate::models::{DomainError, Forge, LineRange, RefKind, RepoInfo};
 use url::Url;
 
-pub fn parse_repository_input(input: &str) -> Result<RepoInfo, DomainError> {
+// Schemes seen in `git remote -v` output and package manager configs
+const REMOTE_SCHEMES: [&str; 5] = [
+    "ssh://",
+    "git+ssh://",
+    "git://",
+    "git+https://",
+    "git+http://",
+];
+
+/// `web_host` is the host from the client profile (`github.com` or a GHES address)
+pub fn parse_repository_input(input: &str, web_host: &str) -> Result<RepoInfo, DomainError> {
     let normalized = input.trim();
+    if normalized.is_empty() {
+        return Err(DomainError::InvalidRepo(
+            "input is empty; expected owner/repo or a repository URL".into(),
+        ));
+    }
+
+    if let Some(web_url) = remote_to_web_url(normalized, web_host)? {
+        return parse_github_url(&web_url, normalized, web_host);
+    }
+
+    if is_host_url(normalized, web_host) {
+        return parse_github_url(normalized, normalized, web_host);
+    }
 
-    if normalized.contains("github.com") {
-        return parse_github_url(normalized);
+    if let Some(rest) = strip_web_scheme(normalized) {
+        return Err(foreign_host(normalized, url_host(rest), web_host));
     }
 
-    parse_owner_repo_pair(normalized)
+    parse_owner_repo_pair(
+        normalized.strip_prefix("gh:").unwrap_or(normalized),
+        web_host,
+    )
+    .map(|info| RepoInfo {
+        original_url: normalized.to_string(),
+        ..info
+    })
 }
 
-fn parse_github_url(normalized: &str) -> Result<RepoInfo, DomainError> {
-    // Accept inputs without scheme (e.g. github.com/owner/repo) by prefixing https://
-    let candidate = if normalized.starts_with("http://") || normalized.starts_with("https://") {
-        normalized.to_string()
-    } else {
-        format!(
-            "https://{}",
-            normalized
-                .trim_start_matches("https://")
-                .trim_start_matches("http://")
-        )
+pub fn invalid(input: &str, reason: &str) -> DomainError {
+    DomainError::InvalidRepo(format!("\"{input}\": {reason}"))
+}
+
+fn foreign_host(input: &str, host: &str, web_host: &str) -> DomainError {
+    invalid(
+        input,
+        &format!("host {host} does not match the configured GitHub host {web_host}"),
+    )
+}
+
+pub fn same_host(host: &str, web_host: &str) -> bool {
+    let strip = |h: &str| {
+        let h = h.trim_end_matches('/').to_ascii_lowercase();
+        h.strip_prefix("www.").map(str::to_string).unwrap_or(h)
+    };
+    strip(host) == strip(web_host)
+}
+
+/// Turns SSH and git remotes (`git@host:owner/repo.git`, `ssh://git@host:22/owner/repo`,
+/// `git+https://host/owner/repo`) into the web URL of the same repository.
+/// `None` means the input does not look like a remote address.
+pub fn remote_to_web_url(input: &str, web_host: &str) -> Result<Option<String>, DomainError> {
+    let web_host = web_host.trim_end_matches('/');
+
+    if REMOTE_SCHEMES
+        .iter()
+        .any(|scheme| input.starts_with(scheme))
+    {
+        let url =
+            Url::parse(input).map_err(|_| invalid(input, "the remote URL could not be parsed"))?;
+        let host = url.host_str().unwrap_or_default();
+        if !same_host(host, web_host) {
+            return Err(foreign_host(input, host, web_host));
+        }
+        return Ok(Some(format!("https://{web_host}{}", url.path())));
+    }
+
+    // scp-like form: [user@]host:owner/repo.git; no '/' before the ':'
+    if input.contains("://") {
+        return Ok(None);
+    }
+    let Some((user_host, path)) = input.split_once(':') else {
+        return Ok(None);
+    };
+    let Some((_, host)) = user_host.rsplit_once('@') else {
+        return Ok(None);
     };
+    if user_host.contains('/') || path.is_empty() {
+        return Ok(None);
+    }
+    if !same_host(host, web_host) {
+        return Err(foreign_host(input, host, web_host));
+    }
+    Ok(Some(format!(
+        "https://{web_host}/{}",
+        path.trim_start_matches('/')
+    )))
+}
+
+pub fn is_host_url(normalized: &str, web_host: &str) -> bool {
+    let without_scheme = strip_web_scheme(normalized).unwrap_or(normalized);
+    same_host(url_host(without_scheme), web_host)
+}
+
+/// Strips `https://` or `http://` in any letter case.
+fn strip_web_scheme(input: &str) -> Option<&str> {
+    ["https://", "http://"].iter().find_map(|scheme| {
+        input
+            .get(..scheme.len())
+            .filter(|prefix| prefix.eq_ignore_ascii_case(scheme))
+            .map(|_| &input[scheme.len()..])
+    })
+}
+
+fn url_host(without_scheme: &str) -> &str {
+    without_scheme
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default()
+}
+
+// GitHub logins are letters, digits and '-' (managed users add a `_shortcode` suffix);
+// repository names also allow '.' and '_'
+fn check_names(input: &str, owner: &str, repo: &str) -> Result<(), DomainError> {
+    let valid = |name: &str, extra: &[char]| {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || extra.contains(&c))
+    };
+    if !valid(owner, &['_']) {
+        return Err(invalid(
+            input,
+            &format!("\"{owner}\" is not a valid owner: use letters, digits, '-' and '_'"),
+        ));
+    }
+    if !valid(repo, &['_', '.']) || repo == "." || repo == ".." {
+        return Err(invalid(
+            input,
+            &format!(
+                "\"{repo}\" is not a valid repository name: use letters, digits, '-', '_' and '.'"
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// `original` is the user's input when `normalized` was derived from an SSH remote.
+fn parse_github_url(
+    normalized: &str,
+    original: &str,
+    web_host: &str,
+) -> Result<RepoInfo, DomainError> {
+    // Accept inputs without scheme (e.g. github.com/owner/repo) by prefixing https://
+    let candidate = format!(
+        "https://{}",
+        strip_web_scheme(normalized).unwrap_or(normalized)
+    );
 
     let url =
-        Url::parse(&candidate).map_err(|_| DomainError::InvalidRepo(normalized.to_string()))?;
+        Url::parse(&candidate).map_err(|_| invalid(original, "the URL could not be parsed"))?;
     let mut segments: Vec<String> = url
         .path_segments()
         .map(|s| s.map(str::to_string).collect())
@@ -33,27 +170,50 @@ fn parse_github_url(normalized: &str) -> Result<RepoInfo, DomainError> {
 
     segments.retain(|s| !s.is_empty());
     if segments.len() < 2 {
-        return Err(DomainError::InvalidRepo(normalized.to_string()));
+        return Err(invalid(
+            original,
+            "the URL must include the owner and repository, e.g. /owner/repo",
+        ));
     }
 
     let owner = segments[0].clone();
-    let repo = segments[1].trim_end_matches(".git").to_string();
+    // github.com/owner/repo@ref means the same as owner/repo@ref
+    let (repo, mut branch) = split_ref_suffix(&segments[1]);
+    let repo = repo.trim_end_matches(".git").to_string();
+    check_names(original, &owner, &repo)?;
 
-    let mut branch: Option<String> = None;
     let mut subdirectory: Option<String> = None;
+    let mut ref_kind: Option<RefKind> = None;
+    let mut ref_path: Option<String> = None;
+    let mut single_file = false;
 
     if segments.len() > 2 {
         match segments[2].as_str() {
+            "commit" if segments.len() > 3 => {
+                branch = Some(segments[3].clone());
+                ref_kind = Some(RefKind::Commit);
+            }
+            // tag names may contain '/'
+            "releases" if segments.len() > 4 && segments[3] == "tag" => {
+                branch = Some(segments[4..].join("/"));
+                ref_kind = Some(RefKind::Tag);
+            }
             "tree" | "blob" | "raw" => {
+                single_file = segments[2] != "tree";
                 if segments.len() > 3 {
                     branch = Some(segments[3].clone());
                 }
                 if segments.len() > 4 {
+                    // splitting at the first segment is only a guess: `feature/auth` is a
+                    // valid branch name too, so the resolver makes the final split
                     subdirectory = Some(segments[4..].join("/"));
+                    ref_path = Some(segments[3..].join("/"));
                 }
             }
             other => {
-                let special = ["issues", "pulls", "pull", "actions", "commits", "releases"];
+                let special = [
+                    "issues", "pulls", "pull", "actions", "commit", "commits", "releases", "tags",
+                ];
                 if !special.contains(&other) && segments.len() > 2 {
                     subdirectory = Some(segments[2..].join("/"));
                 }
@@ -66,21 +226,184 @@ fn parse_github_url(normalized: &str) -> Result<RepoInfo, DomainError> {
         repo,
         branch,
         subdirectory,
-        original_url: normalized.to_string(),
+        original_url: original.to_string(),
+        commit_sha: None,
+        ref_kind,
+        ref_path,
+        single_file,
+        line_range: url
+            .fragment()
+            .filter(|_| single_file)
+            .and_then(parse_line_range),
+        forge: Forge::GitHub,
+        host: Some(web_host.trim_end_matches('/').to_string()),
     })
 }
 
-fn parse_owner_repo_pair(normalized: &str) -> Result<RepoInfo, DomainError> {
-    let parts: Vec<&str> = normalized.split('/').collect();
-    if parts.len() != 2 {
-        return Err(DomainError::InvalidRepo(normalized.to_string()));
+/// `L40`, `L40-L120` or `L40C5-L120C12` (GitHub adds columns on mouse selection);
+/// GitLab writes the range end without `L`: `L40-120`.
+pub fn parse_line_range(fragment: &str) -> Option<LineRange> {
+    fn line(part: &str) -> Option<usize> {
+        let digits = part.strip_prefix('L').unwrap_or(part);
+        let digits = digits.split_once('C').map_or(digits, |(line, _)| line);
+        digits.parse().ok().filter(|n| *n > 0)
+    }
+
+    if !fragment.starts_with('L') {
+        return None;
     }
+    let (start, end) = match fragment.split_once('-') {
+        Some((start, end)) => (line(start)?, line(end)?),
+        None => {
+            let single = line(fragment)?;
+            (single, single)
+        }
+    };
+    Some(LineRange {
+        start: start.min(end),
+        end: start.max(end),
+    })
+}
+
+pub fn split_ref_suffix(value: &str) -> (&str, Option<String>) {
+    match value.split_once('@') {
+        Some((name, reference)) if !reference.is_empty() => (name, Some(reference.to_string())),
+        _ => (value, None),
+    }
+}
+
+fn parse_owner_repo_pair(normalized: &str, web_host: &str) -> Result<RepoInfo, DomainError> {
+    // A trailing `?tab=readme` or `#readme` is not part of the repository
+    let without_query = normalized
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches('/');
+    // owner/repo@ref: ref may be a branch, tag or SHA; the resolver classifies it
+    let (pair, branch) = split_ref_suffix(without_query);
+    let parts: Vec<&str> = pair.split('/').collect();
+    let [owner, repo] = parts.as_slice() else {
+        return Err(invalid(
+            normalized,
+            &format!(
+                "expected owner/repo, owner/repo@ref, gh:owner/repo, \
+                 https://{web_host}/owner/repo or git@{web_host}:owner/repo.git"
+            ),
+        ));
+    };
+    let repo = repo.trim_end_matches(".git");
+    if owner.is_empty() || repo.is_empty() {
+        return Err(invalid(
+            normalized,
+            "owner and repository name must not be empty",
+        ));
+    }
+    check_names(normalized, owner, repo)?;
 
     Ok(RepoInfo {
-        owner: parts[0].to_string(),
-        repo: parts[1].to_string(),
-        branch: None,
+        owner: owner.to_string(),
+        repo: repo.to_string(),
+        branch,
         subdirectory: None,
         original_url: normalized.to_string(),
+        commit_sha: None,
+        ref_kind: None,
+        ref_path: None,
+        single_file: false,
+        line_range: None,
+        forge: Forge::GitHub,
+        host: Some(web_host.trim_end_matches('/').to_string()),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: usize, end: usize) -> Option<LineRange> {
+        Some(LineRange { start, end })
+    }
+
+    #[test]
+    fn parses_single_line_and_ranges() {
+        assert_eq!(parse_line_range("L40"), range(40, 40));
+        assert_eq!(parse_line_range("L40-L120"), range(40, 120));
+        assert_eq!(parse_line_range("L40C5-L120C12"), range(40, 120));
+        assert_eq!(parse_line_range("L40-120"), range(40, 120));
+    }
+
+    #[test]
+    fn orders_reversed_range() {
+        assert_eq!(parse_line_range("L120-L40"), range(40, 120));
+    }
+
+    #[test]
+    fn rejects_non_line_fragments() {
+        for fragment in ["", "readme", "40", "L", "L0", "L-5", "L40-", "L40-Lx", "Lx"] {
+            assert_eq!(parse_line_range(fragment), None, "{fragment:?}");
+        }
+    }
+
+    #[test]
+    fn accepts_largest_line_number() {
+        let max = usize::MAX.to_string();
+        assert_eq!(
+            parse_line_range(&format!("L1-L{max}")),
+            range(1, usize::MAX)
+        );
+    }
+
+    #[test]
+    fn parses_each_input_form() {
+        let ok = [
+            "owner/repo",
+            "owner/repo.git",
+            "gh:owner/repo",
+            "github.com/owner/repo",
+            "www.github.com/owner/repo",
+            "https://github.com/owner/repo",
+            "http://github.com/owner/repo/",
+            "https://GitHub.com/owner/repo",
+            "HTTPS://github.com/owner/repo",
+            "https://github.com/owner/repo?tab=readme",
+            "git@github.com:owner/repo.git",
+            "ssh://git@github.com/owner/repo",
+            "git+https://github.com/owner/repo.git",
+        ];
+        for input in ok {
+            let info = parse_repository_input(input, "github.com")
+                .unwrap_or_else(|err| panic!("{input}: {err}"));
+            assert_eq!(
+                (info.owner.as_str(), info.repo.as_str()),
+                ("owner", "repo"),
+                "{input}"
+            );
+        }
+
+        let rejected = [
+            "",
+            "owner",
+            "github.com:owner/repo",
+            "owner:x/repo",
+            "own er/repo",
+            "owner/re po",
+            "owner/..",
+            "https://gitlab.example/owner/repo",
+            "git@example.com:owner/repo.git",
+        ];
+        for input in rejected {
+            assert!(
+                parse_repository_input(input, "github.com").is_err(),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_refs_and_names_with_dots_and_underscores() {
+        let info = parse_repository_input("my-org_acme/my.repo_v2@v1.0", "github.com").unwrap();
+        assert_eq!(info.owner, "my-org_acme");
+        assert_eq!(info.repo, "my.repo_v2");
+        assert_eq!(info.branch.as_deref(), Some("v1.0"));
+    }
+}
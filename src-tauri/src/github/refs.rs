@@ -0,0 +1,172 @@
+use tokio_util::sync::CancellationToken;
+
+use crate::github::client::GitHubClient;
+use crate::models::{DomainError, RefKind};
+use crate::refs::{SplitRef, looks_like_sha};
+
+/// Полное имя ref для запросов по коммиту: `commits/{name}` без пространства имён
+/// может достаться одноимённой ветке вместо тега.
+pub fn qualified_ref(name: &str, kind: RefKind) -> String {
+    match kind {
+        RefKind::Branch => format!("refs/heads/{name}"),
+        RefKind::Tag => format!("refs/tags/{name}"),
+        RefKind::Commit => name.to_string(),
+    }
+}
+
+/// Тег важнее ветки с тем же именем (git проверяет `refs/tags` раньше `refs/heads`),
+/// SHA — последний вариант.
+pub async fn classify_ref(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    name: &str,
+    cancel: &CancellationToken,
+) -> Result<RefKind, DomainError> {
+    if client
+        .ref_exists(owner, repo, &format!("tags/{name}"), cancel)
+        .await?
+    {
+        return Ok(RefKind::Tag);
+    }
+    if client
+        .ref_exists(owner, repo, &format!("heads/{name}"), cancel)
+        .await?
+    {
+        return Ok(RefKind::Branch);
+    }
+    if looks_like_sha(name) {
+        return Ok(RefKind::Commit);
+    }
+
+    Err(DomainError::NotFound)
+}
+
+/// Делит `feature/auth/src/api` из URL `/tree/...` на ref и путь. Кандидаты — ветки и
+/// теги, чьё имя совпадает с префиксом из целых сегментов. Если кандидатов несколько,
+/// остаются те, в чьём дереве путь существует; если и таких несколько — ошибка со списком.
+pub async fn split_ref_path(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    ref_path: &str,
+    cancel: &CancellationToken,
+) -> Result<SplitRef, DomainError> {
+    let first_segment = ref_path.split('/').next().unwrap_or_default();
+
+    let mut refs = Vec::new();
+    for (namespace, kind) in [("tags", RefKind::Tag), ("heads", RefKind::Branch)] {
+        let names = client
+            .matching_refs(owner, repo, &format!("{namespace}/{first_segment}"), cancel)
+            .await?;
+        refs.extend(names.into_iter().map(|name| (kind, name)));
+    }
+    let mut candidates = ref_candidates(ref_path, refs);
+
+    if candidates.is_empty() && looks_like_sha(first_segment) {
+        let rest = ref_path[first_segment.len()..].trim_start_matches('/');
+        return Ok(SplitRef {
+            name: first_segment.to_string(),
+            kind: RefKind::Commit,
+            path: Some(rest.to_string()).filter(|p| !p.is_empty()),
+        });
+    }
+
+    if candidates.len() > 1 {
+        let mut existing = Vec::new();
+        for candidate in candidates.iter() {
+            let exists = match &candidate.path {
+                Some(path) => {
+                    client
+                        .path_exists(owner, repo, &candidate.name, path, cancel)
+                        .await?
+                }
+                None => true,
+            };
+            if exists {
+                existing.push(candidate.name.clone());
+            }
+        }
+
+        if existing.len() > 1 {
+            return Err(DomainError::AmbiguousRef(existing));
+        }
+        candidates.retain(|c| existing.contains(&c.name));
+    }
+
+    // Самый длинный подходящий префикс — если после проверки пути кто-то остался
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.name.len()));
+    candidates.into_iter().next().ok_or(DomainError::NotFound)
+}
+
+// Ref, чьё имя — префикс `ref_path` из целых сегментов; `refs` — теги раньше веток
+fn ref_candidates(ref_path: &str, refs: Vec<(RefKind, String)>) -> Vec<SplitRef> {
+    let mut candidates: Vec<SplitRef> = Vec::new();
+    for (kind, name) in refs {
+        let Some(rest) = ref_path.strip_prefix(name.as_str()) else {
+            continue;
+        };
+        if !(rest.is_empty() || rest.starts_with('/')) {
+            continue;
+        }
+        // тег и ветка с одинаковым именем — один кандидат, тег в приоритете, как в git
+        if candidates.iter().any(|c| c.name == name) {
+            continue;
+        }
+        let path = Some(rest.trim_start_matches('/').to_string()).filter(|p| !p.is_empty());
+        candidates.push(SplitRef { name, kind, path });
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn qualifies_refs_by_kind() {
+        assert_eq!(qualified_ref("main", RefKind::Branch), "refs/heads/main");
+        assert_eq!(
+            qualified_ref("release/v1", RefKind::Tag),
+            "refs/tags/release/v1"
+        );
+        assert_eq!(qualified_ref("a1b2c3d", RefKind::Commit), "a1b2c3d");
+    }
+
+    #[test]
+    fn picks_refs_that_prefix_the_path_by_whole_segments() {
+        let refs = || {
+            vec![
+                (RefKind::Tag, "v1".to_string()),
+                (RefKind::Tag, "feature".to_string()),
+                (RefKind::Branch, "feature".to_string()),
+                (RefKind::Branch, "feature/auth".to_string()),
+                (RefKind::Branch, "v1.2".to_string()),
+            ]
+        };
+        let split = |name: &str, kind, path: Option<&str>| SplitRef {
+            name: name.into(),
+            kind,
+            path: path.map(Into::into),
+        };
+        let cases = [
+            ("v1", vec![split("v1", RefKind::Tag, None)]),
+            (
+                "v1.2/docs",
+                vec![split("v1.2", RefKind::Branch, Some("docs"))],
+            ),
+            (
+                "feature/auth/src",
+                vec![
+                    split("feature", RefKind::Tag, Some("auth/src")),
+                    split("feature/auth", RefKind::Branch, Some("src")),
+                ],
+            ),
+            ("feature-x/src", vec![]),
+            ("main/src", vec![]),
+        ];
+        for (ref_path, expected) in cases {
+            assert_eq!(ref_candidates(ref_path, refs()), expected, "{ref_path}");
+        }
+    }
+}
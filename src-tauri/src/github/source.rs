@@ -0,0 +1,138 @@
+use tokio_util::sync::CancellationToken;
+
+use crate::github::client::GitHubClient;
+use crate::github::issues::fetch_issues;
+use crate::github::refs::{classify_ref, qualified_ref, split_ref_path};
+use crate::models::{DomainError, FetchStrategy, FileEntry, Issue, RefKind, RepoInfo};
+use crate::source::{
+    FileStream, IssueSource, Source, TreeListing, choose_fetch_strategy, content_ref,
+    extract_tarball,
+};
+
+impl Source for GitHubClient {
+    async fn list_files(
+        &self,
+        input: &RepoInfo,
+        cancel: &CancellationToken,
+    ) -> Result<(RepoInfo, TreeListing), DomainError> {
+        resolve_repository_files(self, input, cancel).await
+    }
+
+    async fn read_file(
+        &self,
+        repo: &RepoInfo,
+        path: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        self.get_raw_content(&repo.owner, &repo.repo, content_ref(repo), path, cancel)
+            .await
+            .map(Some)
+    }
+
+    async fn read_files(
+        &self,
+        repo: &RepoInfo,
+        paths: &[&str],
+        strategy: FetchStrategy,
+        cancel: &CancellationToken,
+    ) -> Result<Option<FileStream>, DomainError> {
+        match choose_fetch_strategy(strategy, paths.len()) {
+            FetchStrategy::Archive => download_archive_contents(self, repo, paths, cancel)
+                .await
+                .map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    // Contents API идёт по симлинку к целевому файлу, поэтому цель читаем из самого blob
+    async fn read_link_target(
+        &self,
+        repo: &RepoInfo,
+        file: &FileEntry,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        match &file.sha {
+            Some(sha) => self
+                .get_blob(&repo.owner, &repo.repo, sha, cancel)
+                .await
+                .map(Some),
+            None => self.read_file(repo, &file.path, cancel).await,
+        }
+    }
+}
+
+impl IssueSource for GitHubClient {
+    async fn fetch_issues(
+        &self,
+        repo: &RepoInfo,
+        cancel: &CancellationToken,
+    ) -> Result<(Vec<Issue>, bool), DomainError> {
+        fetch_issues(self, &repo.owner, &repo.repo, cancel).await
+    }
+}
+
+/// Определяет ветку (по умолчанию — `default_branch` из метаданных репозитория),
+/// фиксирует её коммит и читает дерево именно этого коммита: все дальнейшие
+/// загрузки идут по SHA, так что push во время экспорта не смешает состояния.
+async fn resolve_repository_files(
+    client: &GitHubClient,
+    input: &RepoInfo,
+    cancel: &CancellationToken,
+) -> Result<(RepoInfo, TreeListing), DomainError> {
+    let mut info = input.clone();
+    if let Some(ref_path) = &input.ref_path {
+        let split = split_ref_path(client, &input.owner, &input.repo, ref_path, cancel).await?;
+        info.branch = Some(split.name);
+        info.ref_kind = Some(split.kind);
+        info.subdirectory = split.path;
+    }
+
+    let (branch, ref_kind) = match (&info.branch, info.ref_kind) {
+        (Some(name), Some(kind)) => (name.clone(), kind),
+        (Some(name), None) => {
+            let kind = classify_ref(client, &info.owner, &info.repo, name, cancel).await?;
+            (name.clone(), kind)
+        }
+        (None, _) => {
+            let metadata = client
+                .get_repo_metadata(&input.owner, &input.repo, cancel)
+                .await?;
+            (metadata.default_branch, RefKind::Branch)
+        }
+    };
+
+    let sha = client
+        .resolve_commit_sha(
+            &input.owner,
+            &input.repo,
+            &qualified_ref(&branch, ref_kind),
+            cancel,
+        )
+        .await?;
+    let listing = client
+        .get_tree(&input.owner, &input.repo, &sha, cancel)
+        .await?;
+
+    info.branch = Some(branch);
+    info.commit_sha = Some(sha);
+    info.ref_kind = Some(ref_kind);
+    Ok((info, listing))
+}
+
+/// Скачивает tarball и вынимает из него только файлы, прошедшие фильтрацию по дереву.
+async fn download_archive_contents(
+    client: &GitHubClient,
+    repo_info: &RepoInfo,
+    paths: &[&str],
+    cancel: &CancellationToken,
+) -> Result<FileStream, DomainError> {
+    let archive = client
+        .get_tarball(
+            &repo_info.owner,
+            &repo_info.repo,
+            content_ref(repo_info),
+            cancel,
+        )
+        .await?;
+    Ok(extract_tarball(archive, paths, cancel))
+}
@@ -0,0 +1,241 @@
+use serde::Deserialize;
+use tempfile::NamedTempFile;
+use tokio_util::sync::CancellationToken;
+
+use crate::http::client::{ARCHIVE_TIMEOUT, ApiClient, encode_component, ensure_not_cancelled};
+use crate::models::{DomainError, FileEntry, RefKind, Submodule};
+use crate::source::TreeListing;
+
+const TREE_PER_PAGE: u32 = 100;
+// До 200k записей дерева; дальше список помечается как неполный
+const MAX_TREE_PAGES: usize = 2_000;
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectMetadata {
+    /// У пустого проекта ветки по умолчанию нет
+    pub default_branch: Option<String>,
+    /// `false` — трекер issues в проекте выключен
+    pub issues_enabled: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TreeItem {
+    id: String,
+    path: String,
+    #[serde(rename = "type")]
+    kind: String,
+    mode: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitResponse {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RefResponse {
+    commit: CommitResponse,
+}
+
+/// Клиент GitLab REST API v4 (gitlab.com или self-hosted). `owner` — путь группы,
+/// в том числе вложенной (`group/subgroup`).
+#[derive(Clone)]
+pub struct GitLabClient {
+    api: ApiClient,
+}
+
+impl GitLabClient {
+    pub fn new(api: ApiClient) -> Self {
+        Self { api }
+    }
+
+    pub fn api(&self) -> &ApiClient {
+        &self.api
+    }
+
+    pub fn project_url(&self, owner: &str, repo: &str, path: &str) -> String {
+        let id = encode_component(&format!("{owner}/{repo}"));
+        self.api.api_url(&format!("projects/{id}{path}"))
+    }
+
+    pub async fn get_project(
+        &self,
+        owner: &str,
+        repo: &str,
+        cancel: &CancellationToken,
+    ) -> Result<ProjectMetadata, DomainError> {
+        let url = self.project_url(owner, repo, "");
+        self.api.get_json(&url, "GitLab Projects API", cancel).await
+    }
+
+    /// Коммит, на который указывает ветка, тег или (в том числе сокращённый) SHA;
+    /// `None` — такого ref нет.
+    pub async fn ref_target(
+        &self,
+        owner: &str,
+        repo: &str,
+        kind: RefKind,
+        name: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        let (collection, context) = match kind {
+            RefKind::Branch => ("branches", "GitLab Branches API"),
+            RefKind::Tag => ("tags", "GitLab Tags API"),
+            RefKind::Commit => ("commits", "GitLab Commits API"),
+        };
+        let url = self.project_url(
+            owner,
+            repo,
+            &format!("/repository/{collection}/{}", encode_component(name)),
+        );
+        // Ветка и тег отдают свой коммит во вложенном `commit`, коммит — сам себя
+        let result = match kind {
+            RefKind::Commit => {
+                self.api
+                    .get_json::<CommitResponse>(&url, context, cancel)
+                    .await
+            }
+            _ => self
+                .api
+                .get_json::<RefResponse>(&url, context, cancel)
+                .await
+                .map(|r| r.commit),
+        };
+        match result {
+            Ok(commit) => Ok(Some(commit.id)),
+            Err(DomainError::NotFound) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Полный список файлов ref постранично. Размеров Repository Tree API не отдаёт,
+    /// поэтому `size` всегда 0 — большие файлы отсекаются уже после чтения.
+    pub async fn get_tree(
+        &self,
+        owner: &str,
+        repo: &str,
+        reference: &str,
+        cancel: &CancellationToken,
+    ) -> Result<TreeListing, DomainError> {
+        let mut next = Some(self.project_url(
+            owner,
+            repo,
+            &format!(
+                "/repository/tree?ref={}&recursive=true&per_page={TREE_PER_PAGE}&pagination=keyset",
+                encode_component(reference)
+            ),
+        ));
+        let mut files = Vec::new();
+        let mut submodules = Vec::new();
+        let mut pages = 0usize;
+        let mut truncated = false;
+
+        while let Some(url) = next {
+            ensure_not_cancelled(cancel)?;
+            if pages == MAX_TREE_PAGES {
+                truncated = true;
+                break;
+            }
+            pages += 1;
+
+            let (items, next_url): (Vec<TreeItem>, _) = self
+                .api
+                .get_json_page(&url, "GitLab Repository Tree API", cancel)
+                .await?;
+            for item in items {
+                match item.kind.as_str() {
+                    "blob" => files.push(FileEntry {
+                        path: item.path,
+                        size: 0,
+                        mode: Some(item.mode),
+                        sha: Some(item.id),
+                    }),
+                    // подмодуль: id — закреплённый коммит его репозитория
+                    "commit" => submodules.push(Submodule {
+                        path: item.path,
+                        sha: item.id,
+                        url: None,
+                    }),
+                    _ => {}
+                }
+            }
+            next = next_url;
+        }
+
+        if files.is_empty() {
+            return Err(DomainError::NotFound);
+        }
+
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(TreeListing {
+            files,
+            truncated,
+            submodules,
+            unreadable: 0,
+        })
+    }
+
+    /// Содержимое blob по SHA — для симлинка это путь, на который он указывает.
+    pub async fn get_blob(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+        cancel: &CancellationToken,
+    ) -> Result<String, DomainError> {
+        let url = self.project_url(owner, repo, &format!("/repository/blobs/{sha}/raw"));
+        self.api
+            .get_text(self.api.get(&url), "GitLab Repository Blobs API", cancel)
+            .await
+    }
+
+    pub async fn get_raw_content(
+        &self,
+        owner: &str,
+        repo: &str,
+        reference: &str,
+        path: &str,
+        cancel: &CancellationToken,
+    ) -> Result<String, DomainError> {
+        ensure_not_cancelled(cancel)?;
+
+        let url = self.project_url(
+            owner,
+            repo,
+            &format!(
+                "/repository/files/{}/raw?ref={}",
+                encode_component(path),
+                encode_component(reference)
+            ),
+        );
+        self.api
+            .get_text(self.api.get(&url), "GitLab Repository Files API", cancel)
+            .await
+    }
+
+    /// Скачивает tar.gz-архив проекта на указанном ref; внутри всё лежит в `project-ref-sha/`.
+    pub async fn get_archive(
+        &self,
+        owner: &str,
+        repo: &str,
+        reference: &str,
+        cancel: &CancellationToken,
+    ) -> Result<NamedTempFile, DomainError> {
+        let url = self.project_url(
+            owner,
+            repo,
+            &format!(
+                "/repository/archive.tar.gz?sha={}",
+                encode_component(reference)
+            ),
+        );
+        self.api
+            .download(
+                self.api.get(&url),
+                "GitLab archive",
+                cancel,
+                ARCHIVE_TIMEOUT,
+            )
+            .await
+    }
+}
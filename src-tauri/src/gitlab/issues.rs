@@ -0,0 +1,119 @@
+use serde::Deserialize;
+use tokio_util::sync::CancellationToken;
+
+use crate::gitlab::client::GitLabClient;
+use crate::models::{DomainError, Issue};
+
+#[derive(Debug, Deserialize)]
+struct IssueUser {
+    username: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IssueMilestone {
+    title: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IssueResponse {
+    iid: u64,
+    title: String,
+    state: String,
+    author: Option<IssueUser>,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+    closed_at: Option<String>,
+    user_notes_count: Option<u64>,
+    labels: Option<Vec<String>>,
+    milestone: Option<IssueMilestone>,
+    assignees: Option<Vec<IssueUser>>,
+    description: Option<String>,
+    web_url: Option<String>,
+}
+
+/// Issues проекта; merge requests в GitLab живут в отдельном API и сюда не попадают.
+/// Если трекер в проекте выключен, возвращает `IssuesDisabled`.
+pub async fn fetch_issues(
+    client: &GitLabClient,
+    owner: &str,
+    repo: &str,
+    cancel: &CancellationToken,
+) -> Result<(Vec<Issue>, bool), DomainError> {
+    const PER_PAGE: u32 = 100;
+    const MAX_PAGES: u32 = 10; // до 1000 issues
+    let mut all = Vec::new();
+    let mut truncated = false;
+
+    for page in 1..=MAX_PAGES {
+        if cancel.is_cancelled() {
+            return Err(DomainError::Cancelled);
+        }
+
+        let url = client.project_url(
+            owner,
+            repo,
+            &format!("/issues?state=all&per_page={PER_PAGE}&page={page}"),
+        );
+
+        let page_items: Vec<IssueResponse> = match client
+            .api()
+            .get_json(&url, "GitLab Issues API", cancel)
+            .await
+        {
+            // без трекера GitLab отвечает 403 так же, как на закрытый проект
+            Err(DomainError::Unauthorized) => {
+                let project = client.get_project(owner, repo, cancel).await?;
+                return Err(if project.issues_enabled == Some(false) {
+                    DomainError::IssuesDisabled
+                } else {
+                    DomainError::Unauthorized
+                });
+            }
+            result => result?,
+        };
+
+        if page_items.is_empty() {
+            break;
+        }
+
+        all.extend(page_items.iter().map(to_issue));
+
+        if page_items.len() < PER_PAGE as usize {
+            break;
+        }
+
+        if page == MAX_PAGES {
+            truncated = true;
+        }
+    }
+
+    Ok((all, truncated))
+}
+
+fn to_issue(item: &IssueResponse) -> Issue {
+    Issue {
+        number: item.iid,
+        title: item.title.clone(),
+        // GitLab называет открытые issues `opened`; фильтры экспорта ждут `open`
+        state: match item.state.as_str() {
+            "opened" => "open".to_string(),
+            state => state.to_string(),
+        },
+        author: item.author.as_ref().and_then(|u| u.username.clone()),
+        created_at: item.created_at.clone(),
+        updated_at: item.updated_at.clone(),
+        closed_at: item.closed_at.clone(),
+        comments: item.user_notes_count,
+        labels: item.labels.clone().unwrap_or_default(),
+        milestone: item.milestone.as_ref().and_then(|m| m.title.clone()),
+        assignees: item
+            .assignees
+            .as_deref()
+            .unwrap_or(&[])
+            .iter()
+            .filter_map(|user| user.username.clone())
+            .collect(),
+        body: item.description.clone(),
+        html_url: item.web_url.clone(),
+    }
+}
@@ -0,0 +1,84 @@
+use url::Url;
+
+use crate::github::parser::{invalid, parse_line_range, remote_to_web_url, split_ref_suffix};
+use crate::models::{DomainError, Forge, RefKind, RepoInfo};
+
+/// Адрес проекта GitLab: веб-URL (`https://gitlab.com/group/sub/project/-/tree/main/src`),
+/// SSH-адрес или `host/group/project`. Всё до `/-/` — путь проекта: последний сегмент —
+/// сам проект, остальные — группа с подгруппами.
+pub fn parse_gitlab_input(input: &str, web_host: &str) -> Result<RepoInfo, DomainError> {
+    let normalized = input.trim();
+    let web_url = remote_to_web_url(normalized, web_host)?;
+    let candidate = match &web_url {
+        Some(url) => url.clone(),
+        None if normalized.starts_with("http://") || normalized.starts_with("https://") => {
+            normalized.to_string()
+        }
+        None => format!("https://{normalized}"),
+    };
+
+    let url =
+        Url::parse(&candidate).map_err(|_| invalid(normalized, "the URL could not be parsed"))?;
+    let segments: Vec<String> = url
+        .path_segments()
+        .map(|s| s.filter(|s| !s.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default();
+
+    let (project, rest) = match segments.iter().position(|s| s == "-") {
+        Some(marker) => (&segments[..marker], &segments[marker + 1..]),
+        None => (&segments[..], &[][..]),
+    };
+    let Some((name, group)) = project.split_last().filter(|(_, group)| !group.is_empty()) else {
+        return Err(invalid(
+            normalized,
+            "the URL must include the group and project, e.g. /group/project",
+        ));
+    };
+
+    let (repo, mut branch) = split_ref_suffix(name);
+    let repo = repo.trim_end_matches(".git").to_string();
+
+    let mut subdirectory: Option<String> = None;
+    let mut ref_kind: Option<RefKind> = None;
+    let mut ref_path: Option<String> = None;
+    let mut single_file = false;
+
+    match rest {
+        [view, reference, path @ ..] if ["tree", "blob", "raw"].contains(&view.as_str()) => {
+            single_file = view != "tree";
+            branch = Some(reference.clone());
+            if !path.is_empty() {
+                // как и на GitHub, ветка может содержать '/' — окончательно делит резолвер
+                subdirectory = Some(path.join("/"));
+                ref_path = Some(rest[1..].join("/"));
+            }
+        }
+        [view, sha, ..] if view == "commit" => {
+            branch = Some(sha.clone());
+            ref_kind = Some(RefKind::Commit);
+        }
+        [view, tag @ ..] if view == "tags" && !tag.is_empty() => {
+            branch = Some(tag.join("/"));
+            ref_kind = Some(RefKind::Tag);
+        }
+        _ => {}
+    }
+
+    Ok(RepoInfo {
+        owner: group.join("/"),
+        repo,
+        branch,
+        subdirectory,
+        original_url: normalized.to_string(),
+        commit_sha: None,
+        ref_kind,
+        ref_path,
+        single_file,
+        line_range: url
+            .fragment()
+            .filter(|_| single_file)
+            .and_then(parse_line_range),
+        forge: Forge::GitLab,
+        host: Some(web_host.trim_end_matches('/').to_string()),
+    })
+}
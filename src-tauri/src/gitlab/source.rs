@@ -0,0 +1,107 @@
+use tokio_util::sync::CancellationToken;
+
+use crate::gitlab::client::GitLabClient;
+use crate::gitlab::issues::fetch_issues;
+use crate::models::{DomainError, FetchStrategy, FileEntry, Issue, RefKind, RepoInfo};
+use crate::refs::{RefLookup, resolve_repository_files};
+use crate::source::{
+    FileStream, IssueSource, Source, TreeListing, choose_fetch_strategy, content_ref,
+    extract_tarball,
+};
+
+impl Source for GitLabClient {
+    async fn list_files(
+        &self,
+        input: &RepoInfo,
+        cancel: &CancellationToken,
+    ) -> Result<(RepoInfo, TreeListing), DomainError> {
+        resolve_repository_files(self, input, cancel).await
+    }
+
+    async fn read_file(
+        &self,
+        repo: &RepoInfo,
+        path: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        self.get_raw_content(&repo.owner, &repo.repo, content_ref(repo), path, cancel)
+            .await
+            .map(Some)
+    }
+
+    async fn read_files(
+        &self,
+        repo: &RepoInfo,
+        paths: &[&str],
+        strategy: FetchStrategy,
+        cancel: &CancellationToken,
+    ) -> Result<Option<FileStream>, DomainError> {
+        match choose_fetch_strategy(strategy, paths.len()) {
+            FetchStrategy::Archive => {
+                let archive = self
+                    .get_archive(&repo.owner, &repo.repo, content_ref(repo), cancel)
+                    .await?;
+                Ok(Some(extract_tarball(archive, paths, cancel)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn read_link_target(
+        &self,
+        repo: &RepoInfo,
+        file: &FileEntry,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        match &file.sha {
+            Some(sha) => self
+                .get_blob(&repo.owner, &repo.repo, sha, cancel)
+                .await
+                .map(Some),
+            None => self.read_file(repo, &file.path, cancel).await,
+        }
+    }
+}
+
+impl IssueSource for GitLabClient {
+    async fn fetch_issues(
+        &self,
+        repo: &RepoInfo,
+        cancel: &CancellationToken,
+    ) -> Result<(Vec<Issue>, bool), DomainError> {
+        fetch_issues(self, &repo.owner, &repo.repo, cancel).await
+    }
+}
+
+impl RefLookup for GitLabClient {
+    async fn ref_target(
+        &self,
+        owner: &str,
+        repo: &str,
+        kind: RefKind,
+        name: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        GitLabClient::ref_target(self, owner, repo, kind, name, cancel).await
+    }
+
+    async fn default_branch(
+        &self,
+        owner: &str,
+        repo: &str,
+        cancel: &CancellationToken,
+    ) -> Result<String, DomainError> {
+        let project = self.get_project(owner, repo, cancel).await?;
+        project.default_branch.ok_or(DomainError::NoFiles)
+    }
+
+    async fn get_tree(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+        cancel: &CancellationToken,
+    ) -> Result<TreeListing, DomainError> {
+        GitLabClient::get_tree(self, owner, repo, sha, cancel).await
+    }
+}
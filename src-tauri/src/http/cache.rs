@@ -0,0 +1,124 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::blob_cache::{SizeBudget, touch, write_atomically};
+use crate::local::run_blocking;
+use crate::models::DomainError;
+
+/// Сохранённый ответ GET с валидаторами для условного запроса.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// Следующая страница из заголовка `Link`
+    pub next: Option<String>,
+    pub body: String,
+}
+
+/// Ответы API на диске для запросов с `If-None-Match` / `If-Modified-Since`: на `304`
+/// тело берётся отсюда, а такой ответ GitHub не списывает с лимита запросов.
+/// Как и кэш blob-объектов, ошибки диска считаются промахом.
+#[derive(Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    budget: SizeBudget,
+}
+
+impl ResponseCache {
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        Self {
+            dir,
+            budget: SizeBudget::new(max_bytes),
+        }
+    }
+
+    fn entry_path(&self, url: &str, token: Option<&str>) -> PathBuf {
+        let key = entry_key(url, token);
+        self.dir.join(&key[..2]).join(key)
+    }
+
+    pub async fn load(&self, url: &str, token: Option<&str>) -> Option<CachedResponse> {
+        let path = self.entry_path(url, token);
+        run_blocking(move || {
+            let entry = fs::read_to_string(&path)
+                .ok()
+                .and_then(|raw| serde_json::from_str(&raw).ok());
+            if entry.is_some() {
+                touch(&path);
+            }
+            Ok(entry)
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
+    pub async fn store(&self, url: &str, token: Option<&str>, entry: CachedResponse) {
+        let path = self.entry_path(url, token);
+        let cache = self.clone();
+        let _ = run_blocking(move || {
+            let raw = serde_json::to_string(&entry)
+                .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+            write_atomically(&path, &raw)?;
+            cache.budget.record(&cache.dir, raw.len() as u64)
+        })
+        .await;
+    }
+}
+
+// SHA-256 токена и адреса в hex: ответы для разных токенов не смешиваются,
+// а сам токен на диск не попадает
+fn entry_key(url: &str, token: Option<&str>) -> String {
+    let digest = Sha256::new()
+        .chain_update(token.unwrap_or_default())
+        .chain_update([0])
+        .chain_update(url)
+        .finalize();
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://api.github.com/repos/o/r/git/trees/abc";
+
+    fn response(body: &str) -> CachedResponse {
+        CachedResponse {
+            etag: Some("\"v1\"".into()),
+            last_modified: None,
+            next: None,
+            body: body.into(),
+        }
+    }
+
+    #[test]
+    fn keys_depend_on_url_and_token_without_containing_it() {
+        let anonymous = entry_key(URL, None);
+        let with_token = entry_key(URL, Some("ghp_secret"));
+        assert_eq!(anonymous.len(), 64);
+        assert_eq!(anonymous, entry_key(URL, None));
+        assert_ne!(anonymous, with_token);
+        assert_ne!(anonymous, entry_key(&format!("{URL}?page=2"), None));
+        assert!(with_token.bytes().all(|b| b.is_ascii_hexdigit()));
+        // граница между токеном и адресом не сдвигается
+        assert_ne!(entry_key("bc", Some("a")), entry_key("c", Some("ab")));
+    }
+
+    #[tokio::test]
+    async fn stores_and_loads_responses_per_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(dir.path().to_path_buf(), 1024 * 1024);
+
+        assert!(cache.load(URL, None).await.is_none());
+        cache.store(URL, None, response("{\"tree\":[]}")).await;
+
+        let loaded = cache.load(URL, None).await.unwrap();
+        assert_eq!(loaded.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(loaded.body, "{\"tree\":[]}");
+        assert!(cache.load(URL, Some("token")).await.is_none());
+    }
+}
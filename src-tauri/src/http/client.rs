@@ -0,0 +1,453 @@
+use std::future::Future;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::{Client, RequestBuilder, Response, StatusCode, header};
+use serde::de::DeserializeOwned;
+use tempfile::NamedTempFile;
+use tokio_util::sync::CancellationToken;
+
+use crate::http::cache::{CachedResponse, ResponseCache};
+use crate::http::rate_limit::{rate_limit_reset, status_from_headers, wait_until_reset};
+use crate::http::retry::RetryPolicy;
+use crate::models::{DomainError, HostProfile, RateLimitStatus};
+
+const USER_AGENT: &str = "mergist";
+// Имя пользователя для токена в Basic-авторизации git-эндпоинтов; GitLab требует именно его
+// для OAuth-токенов, остальные хостинги имя не проверяют
+const GIT_TOKEN_USER: &str = "oauth2";
+/// Архив репозитория качается одним запросом и может весить сотни мегабайт
+pub const ARCHIVE_TIMEOUT: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, Default)]
+pub struct ClientConfig {
+    /// Personal access token; without it requests are anonymous (60 req/h, public repos only)
+    pub token: Option<String>,
+    pub host: HostProfile,
+    /// Ждать сброса лимита вместо немедленной ошибки `RateLimited`
+    pub wait_on_rate_limit: bool,
+    pub retry: RetryPolicy,
+}
+
+type RateLimitListener = Arc<dyn Fn(RateLimitStatus) + Send + Sync>;
+
+/// HTTP-клиент REST API хостинга: токен, повторы, таймауты, лимиты и отмена.
+/// Клиенты конкретных хостингов (GitHub, GitLab) строят поверх него свои запросы.
+#[derive(Clone)]
+pub struct ApiClient {
+    client: Arc<Client>,
+    token: Option<Arc<str>>,
+    host: Arc<HostProfile>,
+    wait_on_rate_limit: bool,
+    retry: RetryPolicy,
+    rate_limit_listener: Option<RateLimitListener>,
+    response_cache: Option<ResponseCache>,
+}
+
+impl ApiClient {
+    pub fn new(config: ClientConfig) -> Result<Self, DomainError> {
+        let mut headers = header::HeaderMap::new();
+        headers.insert(
+            header::USER_AGENT,
+            header::HeaderValue::from_static(USER_AGENT),
+        );
+
+        let client = Client::builder()
+            .default_headers(headers)
+            .build()
+            .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+
+        let token = config
+            .token
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .map(Arc::from);
+
+        let host = HostProfile {
+            api_root: config.host.api_root.trim_end_matches('/').to_string(),
+            raw_root: config
+                .host
+                .raw_root
+                .map(|r| r.trim_end_matches('/').to_string())
+                .filter(|r| !r.is_empty()),
+            web_host: config.host.web_host,
+        };
+
+        Ok(Self {
+            client: Arc::new(client),
+            token,
+            host: Arc::new(host),
+            wait_on_rate_limit: config.wait_on_rate_limit,
+            retry: config.retry,
+            rate_limit_listener: None,
+            response_cache: None,
+        })
+    }
+
+    /// Слушатель получает остаток бюджета после каждого ответа API и момент начала ожидания.
+    pub fn with_rate_limit_listener(
+        mut self,
+        listener: impl Fn(RateLimitStatus) + Send + Sync + 'static,
+    ) -> Self {
+        self.rate_limit_listener = Some(Arc::new(listener));
+        self
+    }
+
+    /// JSON-запросы становятся условными: неизменившийся ответ приходит как `304` без тела.
+    pub fn with_response_cache(mut self, cache: ResponseCache) -> Self {
+        self.response_cache = Some(cache);
+        self
+    }
+
+    fn report_rate_limit(&self, status: RateLimitStatus) {
+        if let Some(listener) = &self.rate_limit_listener {
+            listener(status);
+        }
+    }
+
+    pub fn host(&self) -> &HostProfile {
+        self.host.as_ref()
+    }
+
+    pub fn has_token(&self) -> bool {
+        self.token.is_some()
+    }
+
+    pub fn api_url(&self, path: &str) -> String {
+        format!("{}/{}", self.host.api_root, path.trim_start_matches('/'))
+    }
+
+    /// GET-запрос с токеном (если задан). Токен добавляется точечно, а не в default headers,
+    /// чтобы не утекать на сторонние хосты. `user:secret` уходит как Basic-авторизация.
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        let request = self.client.get(url);
+        match self.token.as_deref() {
+            Some(token) => match token.split_once(':') {
+                Some((user, secret)) => request.basic_auth(user, Some(secret)),
+                None => request.bearer_auth(token),
+            },
+            None => request,
+        }
+    }
+
+    /// POST к git-эндпоинту хоста (LFS batch API): там токен принимается только
+    /// паролем Basic-авторизации.
+    pub fn git_post(&self, url: &str) -> RequestBuilder {
+        let request = self.client.post(url);
+        match self.token.as_deref() {
+            Some(token) => match token.split_once(':') {
+                Some((user, secret)) => request.basic_auth(user, Some(secret)),
+                None => request.basic_auth(GIT_TOKEN_USER, Some(token)),
+            },
+            None => request,
+        }
+    }
+
+    /// GET на сторонний хост (например, хранилище LFS) — без токена хостинга.
+    pub fn get_foreign(&self, url: &str) -> RequestBuilder {
+        self.client.get(url)
+    }
+
+    /// Выполняет запрос с повторами при временных сбоях. `read` разбирает тело ответа
+    /// и тоже покрыт повторами: обрыв соединения посреди тела — такой же временный сбой.
+    /// При исчерпанном лимите либо возвращает `RateLimited`, либо (если включено ожидание)
+    /// спит до сброса и начинает заново.
+    async fn execute<T, F, Fut>(
+        &self,
+        request: RequestBuilder,
+        context: &str,
+        cancel: &CancellationToken,
+        timeout: Duration,
+        read: F,
+    ) -> Result<T, DomainError>
+    where
+        F: Fn(Response) -> Fut,
+        Fut: Future<Output = Result<T, DomainError>>,
+    {
+        loop {
+            let result = self
+                .retry
+                .run(cancel, || {
+                    self.attempt(&request, context, cancel, timeout, &read)
+                })
+                .await;
+
+            match result {
+                Err(DomainError::RateLimited { reset_at }) if self.wait_on_rate_limit => {
+                    self.report_rate_limit(RateLimitStatus {
+                        limit: None,
+                        remaining: 0,
+                        reset_at: Some(reset_at),
+                        waiting: true,
+                    });
+                    wait_until_reset(reset_at, cancel).await?;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn attempt<T, F, Fut>(
+        &self,
+        request: &RequestBuilder,
+        context: &str,
+        cancel: &CancellationToken,
+        timeout: Duration,
+        read: &F,
+    ) -> Result<T, DomainError>
+    where
+        F: Fn(Response) -> Fut,
+        Fut: Future<Output = Result<T, DomainError>>,
+    {
+        let request = request
+            .try_clone()
+            .ok_or_else(|| DomainError::Unexpected("Request cannot be repeated".into()))?
+            .timeout(timeout);
+
+        let response = tokio::select! {
+            _ = cancel.cancelled() => return Err(DomainError::Cancelled),
+            result = request.send() => result.map_err(|e| DomainError::Network(e.to_string()))?,
+        };
+
+        if let Some(status) = status_from_headers(response.headers()) {
+            self.report_rate_limit(status);
+        }
+
+        let response = check_status(response, context)?;
+
+        tokio::select! {
+            _ = cancel.cancelled() => Err(DomainError::Cancelled),
+            result = read(response) => result,
+        }
+    }
+
+    pub async fn get_json<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        context: &str,
+        cancel: &CancellationToken,
+    ) -> Result<T, DomainError> {
+        self.get_json_page(url, context, cancel)
+            .await
+            .map(|(value, _)| value)
+    }
+
+    /// Страница списка и адрес следующей из заголовка `Link: <...>; rel="next"`.
+    pub async fn get_json_page<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        context: &str,
+        cancel: &CancellationToken,
+    ) -> Result<(T, Option<String>), DomainError> {
+        let token = self.token.as_deref();
+        let cached = match &self.response_cache {
+            Some(cache) => cache.load(url, token).await,
+            None => None,
+        };
+
+        let mut request = self.get(url);
+        if let Some(entry) = &cached {
+            if let Some(etag) = &entry.etag {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &entry.last_modified {
+                request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let cached = &cached;
+        let keep = self.response_cache.is_some();
+        let timeout = self.retry.request_timeout;
+        let (value, next, fresh) = self
+            .execute(request, context, cancel, timeout, |response| async move {
+                if response.status() == StatusCode::NOT_MODIFIED {
+                    let entry = cached.as_ref().ok_or_else(|| {
+                        DomainError::Unexpected(format!(
+                            "{context} returned 304 to an unconditional request"
+                        ))
+                    })?;
+                    return Ok((parse_json(&entry.body)?, entry.next.clone(), None));
+                }
+
+                let headers = response.headers();
+                let next = next_page_url(headers);
+                let validator = |name| {
+                    headers
+                        .get(name)
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_string)
+                };
+                let etag = validator(header::ETAG);
+                let last_modified = validator(header::LAST_MODIFIED);
+
+                let body = response.text().await.map_err(body_error)?;
+                let value = parse_json(&body)?;
+                let fresh =
+                    (keep && (etag.is_some() || last_modified.is_some())).then(|| CachedResponse {
+                        etag,
+                        last_modified,
+                        next: next.clone(),
+                        body,
+                    });
+                Ok((value, next, fresh))
+            })
+            .await?;
+
+        if let (Some(cache), Some(entry)) = (&self.response_cache, fresh) {
+            cache.store(url, token, entry).await;
+        }
+        Ok((value, next))
+    }
+
+    /// JSON-ответ на заранее собранный запрос, например POST с телом.
+    pub async fn send_json<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+        context: &str,
+        cancel: &CancellationToken,
+    ) -> Result<T, DomainError> {
+        let timeout = self.retry.request_timeout;
+        self.execute(request, context, cancel, timeout, |response| async move {
+            response.json::<T>().await.map_err(body_error)
+        })
+        .await
+    }
+
+    pub async fn get_text(
+        &self,
+        request: RequestBuilder,
+        context: &str,
+        cancel: &CancellationToken,
+    ) -> Result<String, DomainError> {
+        let timeout = self.retry.request_timeout;
+        self.execute(request, context, cancel, timeout, |response| async move {
+            response.text().await.map_err(body_error)
+        })
+        .await
+    }
+
+    /// Тело ответа целиком; `timeout` задаётся отдельно, например для объектов LFS.
+    pub async fn get_bytes(
+        &self,
+        request: RequestBuilder,
+        context: &str,
+        cancel: &CancellationToken,
+        timeout: Duration,
+    ) -> Result<Vec<u8>, DomainError> {
+        self.execute(request, context, cancel, timeout, |response| async move {
+            response
+                .bytes()
+                .await
+                .map(|bytes| bytes.to_vec())
+                .map_err(body_error)
+        })
+        .await
+    }
+
+    /// Тело ответа во временный файл по частям — для архивов, которые не стоит держать
+    /// в памяти целиком. Файл удаляется, когда его закрывают.
+    pub async fn download(
+        &self,
+        request: RequestBuilder,
+        context: &str,
+        cancel: &CancellationToken,
+        timeout: Duration,
+    ) -> Result<NamedTempFile, DomainError> {
+        self.execute(
+            request,
+            context,
+            cancel,
+            timeout,
+            |mut response| async move {
+                let mut file = NamedTempFile::new().map_err(|e| DomainError::Io(e.to_string()))?;
+                while let Some(chunk) = response.chunk().await.map_err(body_error)? {
+                    file.write_all(&chunk)
+                        .map_err(|e| DomainError::Io(e.to_string()))?;
+                }
+                Ok(file)
+            },
+        )
+        .await
+    }
+}
+
+fn next_page_url(headers: &header::HeaderMap) -> Option<String> {
+    let link = headers.get(header::LINK)?.to_str().ok()?;
+    link.split(',').find_map(|part| {
+        let (url, params) = part.split_once(';')?;
+        params
+            .split(';')
+            .any(|param| param.trim() == "rel=\"next\"")
+            .then(|| {
+                url.trim()
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string()
+            })
+    })
+}
+
+/// Кодирует компонент пути целиком, включая '/': GitLab ждёт `group%2Fproject`
+/// вместо числового id и `src%2Fmain.rs` в Repository Files API.
+pub fn encode_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// Кодирует путь по сегментам, оставляя '/' разделителем.
+pub fn encode_path(path: &str) -> String {
+    path.split('/')
+        .map(encode_component)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+pub fn check_status(response: Response, context: &str) -> Result<Response, DomainError> {
+    if let Some(reset_at) = rate_limit_reset(&response) {
+        return Err(DomainError::RateLimited { reset_at });
+    }
+
+    match response.status() {
+        // 422 GitHub возвращает на несуществующий ref/SHA
+        StatusCode::NOT_FOUND | StatusCode::UNPROCESSABLE_ENTITY => Err(DomainError::NotFound),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Err(DomainError::Unauthorized),
+        // 304 бывает только в ответ на условный запрос: тело берётся из кэша ответов
+        StatusCode::NOT_MODIFIED => Ok(response),
+        // 5xx считаются временными и повторяются политикой ретраев
+        status if status.is_server_error() => {
+            Err(DomainError::Network(format!("{context} returned {status}")))
+        }
+        status if !status.is_success() => Err(DomainError::Unexpected(format!(
+            "{context} returned {status}"
+        ))),
+        _ => Ok(response),
+    }
+}
+
+fn parse_json<T: DeserializeOwned>(body: &str) -> Result<T, DomainError> {
+    serde_json::from_str(body).map_err(|e| DomainError::Unexpected(e.to_string()))
+}
+
+fn body_error(err: reqwest::Error) -> DomainError {
+    if err.is_decode() {
+        DomainError::Unexpected(err.to_string())
+    } else {
+        DomainError::Network(err.to_string())
+    }
+}
+
+pub fn ensure_not_cancelled(cancel: &CancellationToken) -> Result<(), DomainError> {
+    if cancel.is_cancelled() {
+        Err(DomainError::Cancelled)
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::HeaderMap;
+use reqwest::{Response, StatusCode};
+use tokio_util::sync::CancellationToken;
+
+use crate::models::{DomainError, RateLimitStatus};
+
+// Если GitHub не сообщил время сброса (secondary limit без Retry-After), ждём минуту
+const FALLBACK_WAIT_SECS: u64 = 60;
+
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok())
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Бюджет запросов из заголовков `X-RateLimit-*`; `None`, если сервер их не прислал
+/// (например, raw-хост).
+pub fn status_from_headers(headers: &HeaderMap) -> Option<RateLimitStatus> {
+    let remaining = header_u64(headers, "x-ratelimit-remaining")?;
+    Some(RateLimitStatus {
+        limit: header_u64(headers, "x-ratelimit-limit"),
+        remaining,
+        reset_at: header_u64(headers, "x-ratelimit-reset"),
+        waiting: false,
+    })
+}
+
+/// Возвращает момент сброса лимита (unix seconds), если ответ — отказ по rate limit.
+/// 403 без исчерпанного бюджета и без `Retry-After` — это отказ в доступе, а не лимит.
+pub fn rate_limit_reset(response: &Response) -> Option<u64> {
+    let status = response.status();
+    if status != StatusCode::FORBIDDEN && status != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+
+    let headers = response.headers();
+    if let Some(retry_after) = header_u64(headers, "retry-after") {
+        return Some(unix_now() + retry_after);
+    }
+
+    let exhausted = header_u64(headers, "x-ratelimit-remaining") == Some(0);
+    if exhausted {
+        return Some(
+            header_u64(headers, "x-ratelimit-reset")
+                .unwrap_or_else(|| unix_now() + FALLBACK_WAIT_SECS),
+        );
+    }
+
+    (status == StatusCode::TOO_MANY_REQUESTS).then(|| unix_now() + FALLBACK_WAIT_SECS)
+}
+
+/// Спит до `reset_at` (плюс секунда запаса), прерываясь по отмене.
+pub async fn wait_until_reset(
+    reset_at: u64,
+    cancel: &CancellationToken,
+) -> Result<(), DomainError> {
+    let wait = reset_at.saturating_sub(unix_now()) + 1;
+    tokio::select! {
+        _ = cancel.cancelled() => Err(DomainError::Cancelled),
+        _ = tokio::time::sleep(Duration::from_secs(wait)) => Ok(()),
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::http::client::ensure_not_cancelled;
+use crate::models::DomainError;
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Общее число попыток, включая первую; 1 — без повторов
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Таймаут одной попытки, включая чтение тела ответа
+    pub request_timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Выполняет `op`, повторяя его при временных сбоях (сеть, таймаут, 5xx)
+    /// с экспоненциальной задержкой. Ожидание между попытками прерывается отменой.
+    pub async fn run<T, F, Fut>(
+        &self,
+        cancel: &CancellationToken,
+        mut op: F,
+    ) -> Result<T, DomainError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, DomainError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            ensure_not_cancelled(cancel)?;
+
+            match op().await {
+                Err(err) if is_transient(&err) && attempt < self.max_attempts => {
+                    let delay = self.backoff(attempt);
+                    tokio::select! {
+                        _ = cancel.cancelled() => return Err(DomainError::Cancelled),
+                        _ = tokio::time::sleep(delay) => {}
+                    }
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    // "Equal jitter": половина задержки фиксирована, половина случайна,
+    // чтобы параллельные загрузки не повторяли запросы синхронно
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_delay);
+        let half = exp / 2;
+        let jitter_ms = random_u64() % (half.as_millis() as u64 + 1);
+        half + Duration::from_millis(jitter_ms)
+    }
+}
+
+fn is_transient(err: &DomainError) -> bool {
+    matches!(err, DomainError::Network(_))
+}
+
+fn random_u64() -> u64 {
+    // RandomState засевается случайными ключами — этого достаточно для джиттера
+    RandomState::new().build_hasher().finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(4),
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors_until_success() {
+        let calls = Cell::new(0);
+        let result = fast_policy(4)
+            .run(&CancellationToken::new(), || {
+                calls.set(calls.get() + 1);
+                let call = calls.get();
+                async move {
+                    if call < 3 {
+                        Err(DomainError::Network("reset".into()))
+                    } else {
+                        Ok(call)
+                    }
+                }
+            })
+            .await;
+        assert!(matches!(result, Ok(3)));
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let calls = Cell::new(0);
+        let result: Result<(), _> = fast_policy(3)
+            .run(&CancellationToken::new(), || {
+                calls.set(calls.get() + 1);
+                async { Err(DomainError::Network("timeout".into())) }
+            })
+            .await;
+        assert!(matches!(result, Err(DomainError::Network(_))));
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_permanent_errors() {
+        let calls = Cell::new(0);
+        let result: Result<(), _> = fast_policy(4)
+            .run(&CancellationToken::new(), || {
+                calls.set(calls.get() + 1);
+                async { Err(DomainError::NotFound) }
+            })
+            .await;
+        assert!(matches!(result, Err(DomainError::NotFound)));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn cancelled_token_stops_before_first_attempt() {
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let calls = Cell::new(0);
+        let result: Result<(), _> = fast_policy(4)
+            .run(&cancel, || {
+                calls.set(calls.get() + 1);
+                async { Ok(()) }
+            })
+            .await;
+        assert!(matches!(result, Err(DomainError::Cancelled)));
+        assert_eq!(calls.get(), 0);
+    }
+
+    #[test]
+    fn backoff_doubles_with_jitter_and_is_capped() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+            ..RetryPolicy::default()
+        };
+        for (attempt, exp) in [
+            (1, 100),
+            (2, 200),
+            (3, 400),
+            (4, 800),
+            (5, 1000),
+            (30, 1000),
+        ] {
+            for _ in 0..20 {
+                let delay = policy.backoff(attempt).as_millis();
+                assert!(
+                    (exp / 2..=exp).contains(&delay),
+                    "attempt {attempt}: {delay}ms outside {}..={exp}ms",
+                    exp / 2
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::time::Duration;
+
+use reqwest::header;
+use serde::Deserialize;
+use tokio_util::sync::CancellationToken;
+
+use crate::http::client::ApiClient;
+use crate::local::run_blocking;
+use crate::models::DomainError;
+
+const POINTER_VERSION: &str = "version https://git-lfs.github.com/spec/v1";
+// Указатель — несколько коротких строк; всё длиннее заведомо настоящий файл
+const MAX_POINTER_LEN: usize = 1024;
+const LFS_MEDIA_TYPE: &str = "application/vnd.git-lfs+json";
+// Объект меньше порога крупных файлов, но хранилище может отвечать медленнее API
+const OBJECT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Указатель Git LFS вместо содержимого файла.
+#[derive(Debug, Clone)]
+pub struct LfsPointer {
+    /// SHA-256 содержимого
+    pub oid: String,
+    pub size: u64,
+}
+
+/// Разбирает текст файла как указатель LFS (`version`, `oid sha256:...`, `size`).
+pub fn parse_pointer(content: &str) -> Option<LfsPointer> {
+    if content.len() > MAX_POINTER_LEN || !content.starts_with(POINTER_VERSION) {
+        return None;
+    }
+
+    let mut oid = None;
+    let mut size = None;
+    for line in content.lines() {
+        if let Some(value) = line.strip_prefix("oid sha256:") {
+            oid = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("size ") {
+            size = value.trim().parse().ok();
+        }
+    }
+
+    let oid = oid.filter(|o| o.len() == 64 && o.bytes().all(|b| b.is_ascii_hexdigit()))?;
+    Some(LfsPointer { oid, size: size? })
+}
+
+#[derive(Deserialize)]
+struct BatchResponse {
+    objects: Vec<BatchObject>,
+}
+
+#[derive(Deserialize)]
+struct BatchObject {
+    #[serde(default)]
+    actions: Option<BatchActions>,
+}
+
+#[derive(Deserialize)]
+struct BatchActions {
+    download: Option<BatchAction>,
+}
+
+#[derive(Deserialize)]
+struct BatchAction {
+    href: String,
+    #[serde(default)]
+    header: HashMap<String, String>,
+}
+
+/// Текст объекта через LFS batch API хостинга (`https://host/owner/repo.git/info/lfs`).
+/// Само содержимое лежит в хранилище на другом хосте: туда уходят только заголовки
+/// из ответа batch API, токен хостинга — нет. `None` — объект недоступен или бинарный.
+pub async fn download_object(
+    api: &ApiClient,
+    lfs_root: &str,
+    pointer: &LfsPointer,
+    cancel: &CancellationToken,
+) -> Result<Option<String>, DomainError> {
+    let body = serde_json::json!({
+        "operation": "download",
+        "transfers": ["basic"],
+        "objects": [{ "oid": pointer.oid, "size": pointer.size }],
+    });
+    let request = api
+        .git_post(&format!("{lfs_root}/objects/batch"))
+        .header(header::ACCEPT, LFS_MEDIA_TYPE)
+        .header(header::CONTENT_TYPE, LFS_MEDIA_TYPE)
+        .body(body.to_string());
+    let batch: BatchResponse = api.send_json(request, "LFS batch API", cancel).await?;
+
+    let Some(action) = batch
+        .objects
+        .into_iter()
+        .find_map(|object| object.actions?.download)
+    else {
+        return Ok(None);
+    };
+
+    let request = action
+        .header
+        .iter()
+        .fold(api.get_foreign(&action.href), |request, (name, value)| {
+            request.header(name, value)
+        });
+    let bytes = api
+        .get_bytes(request, "LFS storage", cancel, OBJECT_TIMEOUT)
+        .await?;
+    Ok(String::from_utf8(bytes).ok())
+}
+
+/// Текст объекта из локального хранилища клона (`.git/lfs/objects/ab/cd/<oid>`).
+pub async fn read_local_object(
+    git_dir: &Path,
+    pointer: &LfsPointer,
+) -> Result<Option<String>, DomainError> {
+    let path = git_dir
+        .join("lfs/objects")
+        .join(&pointer.oid[..2])
+        .join(&pointer.oid[2..4])
+        .join(&pointer.oid);
+    run_blocking(move || match fs::read(&path) {
+        Ok(bytes) => Ok(String::from_utf8(bytes).ok()),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(DomainError::Io(err.to_string())),
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OID: &str = "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393";
+
+    fn pointer_text(oid: &str, size: &str) -> String {
+        format!("{POINTER_VERSION}\noid sha256:{oid}\nsize {size}\n")
+    }
+
+    #[test]
+    fn parses_valid_pointer() {
+        let pointer = parse_pointer(&pointer_text(OID, "12345")).unwrap();
+        assert_eq!(pointer.oid, OID);
+        assert_eq!(pointer.size, 12345);
+    }
+
+    #[test]
+    fn accepts_extra_keys_and_crlf() {
+        let text =
+            format!("{POINTER_VERSION}\r\next-0-foo sha256:abc\r\noid sha256:{OID}\r\nsize 7\r\n");
+        assert_eq!(parse_pointer(&text).map(|p| p.size), Some(7));
+    }
+
+    #[test]
+    fn rejects_regular_files_and_broken_pointers() {
+        let cases = [
+            "fn main() {}\n".to_string(),
+            pointer_text(OID, "12345").replacen("version", "versions", 1),
+            pointer_text(&OID[..63], "1"),
+            pointer_text(&OID.replace('4', "g"), "1"),
+            pointer_text(OID, "-1"),
+            format!("{POINTER_VERSION}\noid sha256:{OID}\n"),
+            format!("{POINTER_VERSION}\nsize 1\n"),
+            format!("{}{}", pointer_text(OID, "1"), "x".repeat(MAX_POINTER_LEN)),
+        ];
+        for text in cases {
+            assert!(parse_pointer(&text).is_none(), "{text:?}");
+        }
+    }
+}
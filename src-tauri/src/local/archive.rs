@@ -0,0 +1,267 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use futures::StreamExt;
+use tokio_util::sync::CancellationToken;
+
+use crate::converter::archive::{has_common_top_level, read_tar_gz, read_zip};
+use crate::local::run_blocking;
+use crate::models::{DomainError, FetchStrategy, FileEntry, Forge, RepoInfo};
+use crate::source::{FileStream, Source, TreeListing, stream_files};
+
+#[derive(Debug, Clone, Copy)]
+enum ArchiveFormat {
+    Zip,
+    TarGz,
+}
+
+const EXTENSIONS: [(&str, ArchiveFormat); 3] = [
+    (".tar.gz", ArchiveFormat::TarGz),
+    (".tgz", ArchiveFormat::TarGz),
+    (".zip", ArchiveFormat::Zip),
+];
+
+// (имя без расширения, формат) по имени файла
+fn split_archive_name(path: &Path) -> Option<(String, ArchiveFormat)> {
+    let file_name = path.file_name()?.to_string_lossy().into_owned();
+    let lower = file_name.to_ascii_lowercase();
+    EXTENSIONS.iter().find_map(|(extension, format)| {
+        lower.ends_with(extension).then(|| {
+            (
+                file_name[..file_name.len() - extension.len()].to_string(),
+                *format,
+            )
+        })
+    })
+}
+
+pub fn is_archive(path: &Path) -> bool {
+    path.is_file() && split_archive_name(path).is_some()
+}
+
+/// Содержимое архива (.zip, .tar.gz) без распаковки на диск; общий верхний каталог,
+/// если он есть, отбрасывается.
+pub struct ArchiveSnapshot {
+    pub info: RepoInfo,
+    files: Vec<FileEntry>,
+    path: PathBuf,
+    format: ArchiveFormat,
+    strip_top: bool,
+}
+
+fn read_entries(
+    path: &Path,
+    format: ArchiveFormat,
+    cancel: &CancellationToken,
+    strip_top: bool,
+    keep: impl FnMut(&FileEntry) -> bool,
+    on_file: impl FnMut(FileEntry, String) -> Result<(), DomainError>,
+) -> Result<(), DomainError> {
+    let file = File::open(path).map_err(|e| DomainError::Io(e.to_string()))?;
+    let reader = BufReader::new(file);
+    match format {
+        ArchiveFormat::TarGz => read_tar_gz(reader, cancel, strip_top, keep, on_file),
+        ArchiveFormat::Zip => read_zip(reader, cancel, strip_top, keep, on_file),
+    }
+}
+
+/// Перечисляет файлы архива: тела записей на этом шаге не читаются.
+pub async fn load_archive_snapshot(
+    path: &Path,
+    cancel: &CancellationToken,
+) -> Result<ArchiveSnapshot, DomainError> {
+    let (name, format) = split_archive_name(path).ok_or_else(|| {
+        DomainError::Io(format!(
+            "{} is not a .zip, .tar.gz or .tgz archive",
+            path.display()
+        ))
+    })?;
+    let path = path.to_path_buf();
+    let cancel = cancel.clone();
+
+    run_blocking(move || {
+        let mut entries = Vec::new();
+        read_entries(
+            &path,
+            format,
+            &cancel,
+            false,
+            |entry| {
+                entries.push(entry.clone());
+                false
+            },
+            |_, _| Ok(()),
+        )?;
+
+        let strip_top = has_common_top_level(entries.iter().map(|e| e.path.as_str()));
+        let mut files: Vec<FileEntry> = entries
+            .into_iter()
+            .filter_map(|entry| {
+                let path = if strip_top {
+                    entry.path.split_once('/')?.1.to_string()
+                } else {
+                    entry.path
+                };
+                Some(FileEntry {
+                    path,
+                    size: entry.size,
+                    mode: None,
+                    sha: None,
+                })
+            })
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        // Владельца у архива нет: в шапке и имени выходного файла — только имя архива
+        let info = RepoInfo {
+            owner: String::new(),
+            repo: name,
+            branch: None,
+            subdirectory: None,
+            original_url: path.display().to_string(),
+            commit_sha: None,
+            ref_kind: None,
+            ref_path: None,
+            single_file: false,
+            line_range: None,
+            forge: Forge::default(),
+            host: None,
+        };
+
+        Ok(ArchiveSnapshot {
+            info,
+            files,
+            path,
+            format,
+            strip_top,
+        })
+    })
+    .await
+}
+
+impl Source for ArchiveSnapshot {
+    async fn list_files(
+        &self,
+        _input: &RepoInfo,
+        _cancel: &CancellationToken,
+    ) -> Result<(RepoInfo, TreeListing), DomainError> {
+        let listing = TreeListing {
+            files: self.files.clone(),
+            truncated: false,
+            submodules: Vec::new(),
+            unreadable: 0,
+        };
+        Ok((self.info.clone(), listing))
+    }
+
+    async fn read_file(
+        &self,
+        _repo: &RepoInfo,
+        path: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        match self.stream_entries(&[path], cancel).next().await {
+            Some(entry) => entry.map(|(_, content)| Some(content)),
+            None => Ok(None),
+        }
+    }
+
+    // Каждое чтение — проход по архиву, поэтому всегда читаем пакетом
+    async fn read_files(
+        &self,
+        _repo: &RepoInfo,
+        paths: &[&str],
+        _strategy: FetchStrategy,
+        cancel: &CancellationToken,
+    ) -> Result<Option<FileStream>, DomainError> {
+        Ok(Some(self.stream_entries(paths, cancel)))
+    }
+
+    fn header_lines(&self, repo: &RepoInfo) -> String {
+        format!("- **Archive:** {}\n", repo.original_url)
+    }
+}
+
+impl ArchiveSnapshot {
+    /// Указанные файлы по мере распаковки: архив читается, пока поток не закрыт.
+    fn stream_entries(&self, paths: &[&str], cancel: &CancellationToken) -> FileStream {
+        let wanted: HashSet<String> = paths.iter().map(|p| p.to_string()).collect();
+        let (path, format, strip_top) = (self.path.clone(), self.format, self.strip_top);
+        let cancel = cancel.clone();
+
+        stream_files(move |send| {
+            read_entries(
+                &path,
+                format,
+                &cancel,
+                strip_top,
+                |entry| wanted.contains(&entry.path),
+                |file, content| send(file.path, content),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::archive::tests::{tar_gz, zip};
+
+    const FILES: [(&str, &str); 3] = [
+        ("drop-2024/README.md", "readme\n"),
+        ("drop-2024/src/main.rs", "fn main() {}\n"),
+        ("drop-2024/src/lib.rs", "pub fn lib() {}\n"),
+    ];
+
+    #[tokio::test]
+    async fn lists_and_streams_zip_and_tar_gz_without_top_level_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let cancel = CancellationToken::new();
+        for (name, bytes) in [("drop.tar.gz", tar_gz(&FILES)), ("Drop.ZIP", zip(&FILES))] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, bytes).unwrap();
+            assert!(is_archive(&path));
+
+            let snapshot = load_archive_snapshot(&path, &cancel).await.unwrap();
+            assert_eq!(snapshot.info.repo, name.split('.').next().unwrap());
+            let paths: Vec<&str> = snapshot.files.iter().map(|f| f.path.as_str()).collect();
+            assert_eq!(paths, ["README.md", "src/lib.rs", "src/main.rs"], "{name}");
+
+            let stream = snapshot
+                .read_files(
+                    &snapshot.info,
+                    &["src/main.rs", "README.md"],
+                    FetchStrategy::Auto,
+                    &cancel,
+                )
+                .await
+                .unwrap()
+                .unwrap();
+            let files: Vec<(String, String)> = stream.map(Result::unwrap).collect().await;
+            assert_eq!(
+                files,
+                [
+                    ("README.md".to_string(), "readme\n".to_string()),
+                    ("src/main.rs".to_string(), "fn main() {}\n".to_string()),
+                ],
+                "{name}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn keeps_paths_when_entries_have_no_common_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("flat.tgz");
+        std::fs::write(&path, tar_gz(&[("a.txt", "a\n"), ("docs/b.md", "b\n")])).unwrap();
+
+        let snapshot = load_archive_snapshot(&path, &CancellationToken::new())
+            .await
+            .unwrap();
+        let paths: Vec<&str> = snapshot.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["a.txt", "docs/b.md"]);
+        assert!(!is_archive(&dir.path().join("flat.tar")));
+    }
+}
@@ -0,0 +1,275 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+use tokio_util::sync::CancellationToken;
+
+use crate::http::client::ensure_not_cancelled;
+use crate::local::run_blocking;
+use crate::models::{
+    DomainError, EXECUTABLE_MODE, FileEntry, Forge, REGULAR_MODE, RepoInfo, SYMLINK_MODE,
+};
+use crate::source::{Source, TreeListing};
+
+// Метаданные git — не часть исходников (в worktree `.git` может быть и файлом)
+const SKIPPED_ENTRIES: [&str; 1] = [".git"];
+// Результаты сборки и зависимости пропускаются, даже если их нет в .gitignore
+const SKIPPED_DIRS: [&str; 2] = ["node_modules", "target"];
+
+/// `local/<имя каталога>` — чтобы шапка и имя выходного файла выглядели как у репозитория.
+pub fn local_repo_info(root: &Path) -> RepoInfo {
+    let name = root
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "directory".into());
+
+    RepoInfo {
+        owner: "local".into(),
+        repo: name,
+        branch: None,
+        subdirectory: None,
+        original_url: root.display().to_string(),
+        commit_sha: None,
+        ref_kind: None,
+        ref_path: None,
+        single_file: false,
+        line_range: None,
+        forge: Forge::default(),
+        host: None,
+    }
+}
+
+/// Рабочая копия на диске: файлы читаются как есть, включая незакоммиченные правки.
+pub struct LocalDirectory {
+    root: PathBuf,
+}
+
+impl LocalDirectory {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+        }
+    }
+
+    pub fn info(&self) -> RepoInfo {
+        local_repo_info(&self.root)
+    }
+}
+
+impl Source for LocalDirectory {
+    /// Рекурсивно перечисляет файлы и симлинки каталога с учётом .gitignore; пути
+    /// относительные, через '/', отсортированы. Нечитаемый каталог пропускается.
+    async fn list_files(
+        &self,
+        input: &RepoInfo,
+        cancel: &CancellationToken,
+    ) -> Result<(RepoInfo, TreeListing), DomainError> {
+        let root = self.root.clone();
+        let cancel = cancel.clone();
+        let (files, unreadable) = run_blocking(move || walk(&root, &cancel)).await?;
+        Ok((
+            input.clone(),
+            TreeListing {
+                files,
+                truncated: false,
+                submodules: Vec::new(),
+                unreadable,
+            },
+        ))
+    }
+
+    /// Нечитаемый файл (нет прав, удалён во время обхода) не срывает весь экспорт;
+    /// не-UTF-8 байты заменяются.
+    async fn read_file(
+        &self,
+        _repo: &RepoInfo,
+        path: &str,
+        _cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        let full_path = self.root.join(path);
+        let bytes = run_blocking(move || Ok(fs::read(full_path).ok())).await?;
+        Ok(bytes.map(|bytes| String::from_utf8_lossy(&bytes).into_owned()))
+    }
+
+    async fn read_link_target(
+        &self,
+        _repo: &RepoInfo,
+        file: &FileEntry,
+        _cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        let full_path = self.root.join(&file.path);
+        let target = run_blocking(move || Ok(fs::read_link(full_path).ok())).await?;
+        Ok(target.map(|t| t.to_string_lossy().replace('\\', "/")))
+    }
+
+    fn header_lines(&self, repo: &RepoInfo) -> String {
+        format!("- **Path:** {}\n", repo.original_url)
+    }
+}
+
+// Режим как в дереве git; бит исполнения есть только на Unix
+#[cfg(unix)]
+fn file_mode(metadata: &fs::Metadata) -> &'static str {
+    use std::os::unix::fs::PermissionsExt;
+
+    if metadata.permissions().mode() & 0o111 != 0 {
+        EXECUTABLE_MODE
+    } else {
+        REGULAR_MODE
+    }
+}
+
+#[cfg(not(unix))]
+fn file_mode(_metadata: &fs::Metadata) -> &'static str {
+    REGULAR_MODE
+}
+
+/// Файлы каталога и число записей (каталогов, файлов), которые не удалось прочитать.
+/// Правила .gitignore (вместе с `.git/info/exclude` и глобальными) действуют и вне
+/// клона; скрытые файлы вроде `.github` остаются.
+fn walk(root: &Path, cancel: &CancellationToken) -> Result<(Vec<FileEntry>, u64), DomainError> {
+    if !root.is_dir() {
+        return Err(DomainError::Io(format!(
+            "{} is not a directory",
+            root.display()
+        )));
+    }
+
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            let skipped = SKIPPED_ENTRIES.contains(&name.as_ref())
+                || is_dir && SKIPPED_DIRS.contains(&name.as_ref());
+            !skipped
+        })
+        .build();
+
+    let mut files = Vec::new();
+    let mut unreadable = 0;
+    for entry in walker {
+        ensure_not_cancelled(cancel)?;
+
+        let Ok(entry) = entry else {
+            unreadable += 1;
+            continue;
+        };
+        let Some(file_type) = entry.file_type() else {
+            continue;
+        };
+        if !(file_type.is_file() || file_type.is_symlink()) {
+            continue;
+        }
+        let Ok(relative) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        let path = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        // Симлинк не разыменовывается: метаданные — его собственные
+        let Ok(metadata) = entry.metadata() else {
+            unreadable += 1;
+            continue;
+        };
+        let mode = if file_type.is_symlink() {
+            SYMLINK_MODE
+        } else {
+            file_mode(&metadata)
+        };
+        files.push(FileEntry {
+            path,
+            size: metadata.len(),
+            mode: Some(mode.to_string()),
+            sha: None,
+        });
+    }
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok((files, unreadable))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "x\n").unwrap();
+    }
+
+    fn paths(files: &[FileEntry]) -> Vec<&str> {
+        files.iter().map(|f| f.path.as_str()).collect()
+    }
+
+    #[test]
+    fn respects_gitignore_and_default_excludes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join(".gitignore"), "*.log\nbuild/\n").unwrap();
+        for path in [
+            "src/main.rs",
+            "debug.log",
+            "build/out.txt",
+            "node_modules/pkg/index.js",
+            "target/debug/app",
+            ".git/HEAD",
+            ".github/workflows/ci.yml",
+            "docs/target",
+        ] {
+            write(root, path);
+        }
+
+        let (files, unreadable) = walk(root, &CancellationToken::new()).unwrap();
+        assert_eq!(
+            paths(&files),
+            [
+                ".github/workflows/ci.yml",
+                ".gitignore",
+                "docs/target",
+                "src/main.rs"
+            ]
+        );
+        assert_eq!(unreadable, 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn skips_unreadable_directories() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "open/a.rs");
+        write(root, "locked/b.rs");
+        let locked = root.join("locked");
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+        // root читает каталог и без прав — тогда проверять нечего
+        let denied = fs::read_dir(&locked).is_err();
+
+        let result = walk(root, &CancellationToken::new());
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        let (files, unreadable) = result.unwrap();
+        if denied {
+            assert_eq!(paths(&files), ["open/a.rs"]);
+            assert_eq!(unreadable, 1);
+        }
+    }
+
+    #[test]
+    fn cancelled_walk_stops() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "a.rs");
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        assert!(matches!(
+            walk(dir.path(), &cancel),
+            Err(DomainError::Cancelled)
+        ));
+    }
+}
@@ -0,0 +1,359 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use git2::{ErrorCode, ObjectType, Oid, Repository, TreeWalkMode, TreeWalkResult};
+use tokio_util::sync::CancellationToken;
+
+use crate::http::client::ensure_not_cancelled;
+use crate::lfs::{LfsPointer, read_local_object};
+use crate::local::directory::local_repo_info;
+use crate::local::run_blocking;
+use crate::models::{DomainError, FileEntry, RefKind, RepoInfo, Submodule};
+use crate::source::{Source, TreeListing, ref_lines};
+
+fn git_error(err: git2::Error) -> DomainError {
+    match err.code() {
+        ErrorCode::NotFound => DomainError::NotFound,
+        _ => DomainError::Git(err.message().to_string()),
+    }
+}
+
+/// Дерево коммита локального клона: файлы и их blob-объекты для последующего чтения.
+pub struct GitSnapshot {
+    pub info: RepoInfo,
+    files: Vec<FileEntry>,
+    submodules: Vec<Submodule>,
+    blobs: HashMap<String, Oid>,
+    /// Клон открывается один раз; `Repository` не `Sync`, поэтому чтения идут по очереди
+    repo: Arc<Mutex<Repository>>,
+    /// Общий каталог `.git` (у worktree — основного клона) с хранилищем объектов LFS
+    git_dir: PathBuf,
+}
+
+/// Открывает клон (путь может быть и каталогом внутри него — тогда экспортируется только
+/// он) и фиксирует ref (ветка, тег или SHA) на коммите; рабочая копия не трогается —
+/// файлы читаются прямо из объектов git.
+pub async fn load_git_snapshot(
+    root: &Path,
+    reference: &str,
+    cancel: &CancellationToken,
+) -> Result<GitSnapshot, DomainError> {
+    let root = root.to_path_buf();
+    let reference = reference.to_string();
+    let cancel = cancel.clone();
+    run_blocking(move || {
+        let repo = Repository::discover(&root).map_err(git_error)?;
+        let (kind, oid) = resolve_ref(&repo, &reference)?;
+        let TreeFiles {
+            files,
+            submodules,
+            blobs,
+        } = list_tree(&repo, oid, &cancel)?;
+
+        // Имя и путь — самого клона, даже если открыт каталог внутри него
+        let mut info = local_repo_info(repo.workdir().unwrap_or(&root));
+        if let Some((owner, name)) = origin_owner_repo(&repo) {
+            info.owner = owner;
+            info.repo = name;
+        }
+        info.branch = Some(reference);
+        info.ref_kind = Some(kind);
+        info.commit_sha = Some(oid.to_string());
+        info.subdirectory = subdirectory_in(&repo, &root);
+
+        Ok(GitSnapshot {
+            info,
+            files,
+            submodules,
+            blobs,
+            git_dir: repo.commondir().to_path_buf(),
+            repo: Arc::new(Mutex::new(repo)),
+        })
+    })
+    .await
+}
+
+impl Source for GitSnapshot {
+    async fn list_files(
+        &self,
+        _input: &RepoInfo,
+        _cancel: &CancellationToken,
+    ) -> Result<(RepoInfo, TreeListing), DomainError> {
+        let listing = TreeListing {
+            files: self.files.clone(),
+            truncated: false,
+            submodules: self.submodules.clone(),
+            unreadable: 0,
+        };
+        Ok((self.info.clone(), listing))
+    }
+
+    // Blob читается по своему Oid только тогда, когда до файла дошла очередь записи
+    async fn read_file(
+        &self,
+        _repo: &RepoInfo,
+        path: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        let Some(oid) = self.blobs.get(path).copied() else {
+            return Ok(None);
+        };
+        ensure_not_cancelled(cancel)?;
+        let repo = Arc::clone(&self.repo);
+        run_blocking(move || {
+            let repo = repo.lock().unwrap_or_else(PoisonError::into_inner);
+            let blob = repo.find_blob(oid).map_err(git_error)?;
+            // Не-UTF-8 байты заменяются
+            Ok(Some(String::from_utf8_lossy(blob.content()).into_owned()))
+        })
+        .await
+    }
+
+    fn header_lines(&self, repo: &RepoInfo) -> String {
+        format!("{}- **Path:** {}\n", ref_lines(repo), repo.original_url)
+    }
+
+    // Только то, что `git lfs fetch` уже скачал: в сеть локальный источник не ходит
+    async fn read_lfs_object(
+        &self,
+        _repo: &RepoInfo,
+        pointer: &LfsPointer,
+        _cancel: &CancellationToken,
+    ) -> Result<Option<String>, DomainError> {
+        read_local_object(&self.git_dir, pointer).await
+    }
+}
+
+// Путь каталога внутри рабочей копии (`src/app`); `None` — корень клона
+fn subdirectory_in(repo: &Repository, root: &Path) -> Option<String> {
+    let workdir = repo.workdir()?.canonicalize().ok()?;
+    let root = root.canonicalize().ok()?;
+    let parts: Vec<String> = root
+        .strip_prefix(&workdir)
+        .ok()?
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+// Тег важнее ветки с тем же именем, как в git и удалённых источниках; затем любая ревизия
+fn resolve_ref(repo: &Repository, reference: &str) -> Result<(RefKind, Oid), DomainError> {
+    for (namespace, kind) in [
+        ("refs/tags/", RefKind::Tag),
+        ("refs/heads/", RefKind::Branch),
+    ] {
+        if let Ok(found) = repo.find_reference(&format!("{namespace}{reference}")) {
+            let commit = found.peel_to_commit().map_err(git_error)?;
+            return Ok((kind, commit.id()));
+        }
+    }
+
+    let commit = repo
+        .revparse_single(reference)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(git_error)?;
+    Ok((RefKind::Commit, commit.id()))
+}
+
+struct TreeFiles {
+    files: Vec<FileEntry>,
+    submodules: Vec<Submodule>,
+    blobs: HashMap<String, Oid>,
+}
+
+fn list_tree(
+    repo: &Repository,
+    commit: Oid,
+    cancel: &CancellationToken,
+) -> Result<TreeFiles, DomainError> {
+    let tree = repo
+        .find_commit(commit)
+        .and_then(|c| c.tree())
+        .map_err(git_error)?;
+    let odb = repo.odb().map_err(git_error)?;
+
+    let mut files = Vec::new();
+    let mut submodules = Vec::new();
+    let mut blobs = HashMap::new();
+    let mut failure = None;
+    tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+        if cancel.is_cancelled() {
+            failure = Some(DomainError::Cancelled);
+            return TreeWalkResult::Abort;
+        }
+        let Some(name) = entry.name() else {
+            return TreeWalkResult::Ok;
+        };
+        // Подмодуль — ссылка на коммит другого репозитория; URL добавит `.gitmodules`
+        if entry.kind() == Some(ObjectType::Commit) {
+            submodules.push(Submodule {
+                path: format!("{dir}{name}"),
+                sha: entry.id().to_string(),
+                url: None,
+            });
+            return TreeWalkResult::Ok;
+        }
+        if entry.kind() != Some(ObjectType::Blob) {
+            return TreeWalkResult::Ok;
+        }
+
+        // Размер берём из заголовка объекта, не распаковывая содержимое
+        match odb.read_header(entry.id()) {
+            Ok((size, _)) => {
+                let path = format!("{dir}{name}");
+                blobs.insert(path.clone(), entry.id());
+                files.push(FileEntry {
+                    path,
+                    size: size as u64,
+                    mode: Some(format!("{:o}", entry.filemode())),
+                    sha: Some(entry.id().to_string()),
+                });
+                TreeWalkResult::Ok
+            }
+            Err(err) => {
+                failure = Some(git_error(err));
+                TreeWalkResult::Abort
+            }
+        }
+    })
+    .map_err(|err| failure.take().unwrap_or_else(|| git_error(err)))?;
+
+    if let Some(err) = failure {
+        return Err(err);
+    }
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(TreeFiles {
+        files,
+        submodules,
+        blobs,
+    })
+}
+
+/// `owner/repo` из адреса `origin`: два последних сегмента пути в любой форме
+/// (`https://host/o/r.git`, `git@host:o/r.git`, `ssh://git@host/o/r`).
+fn origin_owner_repo(repo: &Repository) -> Option<(String, String)> {
+    let remote = repo.find_remote("origin").ok()?;
+    let url = remote.url()?.trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+
+    let mut segments = url.rsplit(['/', ':']).filter(|s| !s.is_empty());
+    let name = segments.next()?;
+    let owner = segments.next()?;
+    Some((owner.to_string(), name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use git2::{IndexAddOption, Signature};
+
+    use super::*;
+
+    // Коммит рабочей копии целиком поверх HEAD
+    fn commit_all(repo: &Repository, files: &[(&str, &str)], message: &str) -> Oid {
+        let workdir = repo.workdir().unwrap();
+        for (path, content) in files {
+            let path = workdir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], IndexAddOption::DEFAULT, None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .unwrap()
+    }
+
+    async fn read(snapshot: &GitSnapshot, path: &str) -> Option<String> {
+        snapshot
+            .read_file(&snapshot.info, path, &CancellationToken::new())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reads_branches_tags_and_commits_without_checkout() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        repo.remote("origin", "git@example.com:acme/widgets.git")
+            .unwrap();
+        let first = commit_all(
+            &repo,
+            &[("README.md", "v1\n"), ("src/lib.rs", "lib\n")],
+            "first",
+        );
+        let object = repo.find_object(first, None).unwrap();
+        repo.tag_lightweight("v1", &object, false).unwrap();
+        let second = commit_all(&repo, &[("README.md", "v2\n")], "second");
+        repo.branch("stable", &repo.find_commit(first).unwrap(), false)
+            .unwrap();
+        let head = repo.head().unwrap().shorthand().unwrap().to_string();
+        // незакоммиченная правка в экспорт не попадает
+        fs::write(dir.path().join("README.md"), "dirty\n").unwrap();
+
+        let cancel = CancellationToken::new();
+        let snapshot = load_git_snapshot(dir.path(), &head, &cancel).await.unwrap();
+        assert_eq!(
+            (snapshot.info.owner.as_str(), snapshot.info.repo.as_str()),
+            ("acme", "widgets")
+        );
+        assert_eq!(snapshot.info.ref_kind, Some(RefKind::Branch));
+        assert_eq!(snapshot.info.commit_sha, Some(second.to_string()));
+        let paths: Vec<&str> = snapshot.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["README.md", "src/lib.rs"]);
+        assert_eq!(read(&snapshot, "README.md").await.as_deref(), Some("v2\n"));
+
+        let tagged = load_git_snapshot(dir.path(), "v1", &cancel).await.unwrap();
+        assert_eq!(tagged.info.ref_kind, Some(RefKind::Tag));
+        assert_eq!(read(&tagged, "README.md").await.as_deref(), Some("v1\n"));
+
+        let short_sha = &first.to_string()[..7];
+        let pinned = load_git_snapshot(dir.path(), short_sha, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(pinned.info.ref_kind, Some(RefKind::Commit));
+        assert_eq!(pinned.info.commit_sha, Some(first.to_string()));
+
+        let branch = load_git_snapshot(&dir.path().join("src"), "stable", &cancel)
+            .await
+            .unwrap();
+        assert_eq!(branch.info.subdirectory.as_deref(), Some("src"));
+
+        assert!(matches!(
+            load_git_snapshot(dir.path(), "missing", &cancel).await,
+            Err(DomainError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn tags_win_over_branches_with_the_same_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let first = commit_all(&repo, &[("a.txt", "1\n")], "first");
+        repo.tag_lightweight("release", &repo.find_object(first, None).unwrap(), false)
+            .unwrap();
+        let second = commit_all(&repo, &[("a.txt", "2\n")], "second");
+        repo.branch("release", &repo.find_commit(second).unwrap(), false)
+            .unwrap();
+
+        assert_eq!(
+            resolve_ref(&repo, "release").unwrap(),
+            (RefKind::Tag, first)
+        );
+    }
+}
@@ -0,0 +1,14 @@
+pub mod archive;
+pub mod directory;
+pub mod git;
+
+use crate::models::DomainError;
+
+/// Обход каталогов, git-объекты и архивы — синхронная работа с диском, не держим ею async-рантайм.
+pub async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, DomainError> + Send + 'static,
+) -> Result<T, DomainError> {
+    tokio::task::spawn_blocking(task)
+        .await
+        .map_err(|e| DomainError::Unexpected(e.to_string()))?
+}
@@ -1,13 +1,25 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod bitbucket;
+mod blob_cache;
 mod commands;
 mod converter;
+mod forge;
+mod gitea;
 mod github;
+mod gitlab;
+mod http;
+mod lfs;
+mod local;
 mod models;
+mod refs;
+mod settings;
+mod source;
 
 use commands::{
-    ConversionState, cancel_conversion, convert_repo_to_markdown, export_issues, get_file_size,
-    read_file_chunk,
+    ConversionState, cancel_conversion, clear_cache, convert_local_to_markdown,
+    convert_repo_to_markdown, export_issues, get_file_size, get_settings, read_file_chunk,
+    save_settings,
 };
 #[cfg(debug_assertions)]
 use tauri::Manager;
@@ -21,10 +33,14 @@ fn main() {
         .plugin(tauri_plugin_updater::Builder::new().build())
         .invoke_handler(tauri::generate_handler![
             convert_repo_to_markdown,
+            convert_local_to_markdown,
             export_issues,
             cancel_conversion,
             read_file_chunk,
-            get_file_size
+            get_file_size,
+            get_settings,
+            save_settings,
+            clear_cache
         ])
         .setup(|app| {
             #[cfg(not(debug_assertions))]
@@ -1,4 +1,11 @@
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+/// Git tree modes that change how a file is exported
+pub const SYMLINK_MODE: &str = "120000";
+pub const EXECUTABLE_MODE: &str = "100755";
+pub const REGULAR_MODE: &str = "100644";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RepoInput {
@@ -7,6 +14,17 @@ pub struct RepoInput {
     pub output_path: Option<String>,
 }
 
+/// A directory, local clone or .zip/.tar.gz archive to convert instead of a GitHub repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalInput {
+    pub path: String,
+    /// Branch, tag or commit of a local clone; without it the working copy is read as is
+    #[serde(default)]
+    pub reference: Option<String>,
+    #[serde(default)]
+    pub output_path: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RepoInfo {
     pub owner: String,
@@ -16,12 +34,110 @@ pub struct RepoInfo {
     #[serde(default)]
     pub subdirectory: Option<String>,
     pub original_url: String,
+    /// Commit the export is pinned to, resolved from `branch`
+    #[serde(default)]
+    pub commit_sha: Option<String>,
+    /// What `branch` names; `None` until resolved unless the input made it explicit
+    #[serde(default)]
+    pub ref_kind: Option<RefKind>,
+    /// Everything after `/tree/` (or `/blob/`, `/raw/`) when it has several segments:
+    /// the branch may itself contain `/`, so the resolver splits it against real refs
+    #[serde(default)]
+    pub ref_path: Option<String>,
+    /// `subdirectory` is a single file (a `/blob/` or `/raw/` URL). Gitea and Bitbucket
+    /// `/src/` URLs open both files and directories, so the processor clears the flag
+    /// when the tree has no such file
+    #[serde(default)]
+    pub single_file: bool,
+    /// Lines from a `#L40-L120` fragment, 1-based and inclusive
+    #[serde(default)]
+    pub line_range: Option<LineRange>,
+    /// Meaningful only together with `host`
+    #[serde(default)]
+    pub forge: Forge,
+    /// Web host from the input, e.g. `gitlab.com`; `None` for local sources
+    #[serde(default)]
+    pub host: Option<String>,
+}
+
+/// Code hosting a remote repository lives on
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Forge {
+    #[default]
+    GitHub,
+    GitLab,
+    /// Gitea and its fork Forgejo, e.g. codeberg.org
+    Gitea,
+    /// Bitbucket Cloud
+    Bitbucket,
+}
+
+impl Forge {
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Forge::GitHub => "GitHub",
+            Forge::GitLab => "GitLab",
+            Forge::Gitea => "Gitea",
+            Forge::Bitbucket => "Bitbucket",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RefKind {
+    Branch,
+    Tag,
+    Commit,
+}
+
+impl RefKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RefKind::Branch => "branch",
+            RefKind::Tag => "tag",
+            RefKind::Commit => "commit",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileEntry {
     pub path: String,
     pub size: u64,
+    /// Git file mode from the tree, e.g. `100644`, `100755` or `120000`
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// Blob SHA; `None` for sources without git objects
+    #[serde(default)]
+    pub sha: Option<String>,
+}
+
+impl FileEntry {
+    pub fn is_symlink(&self) -> bool {
+        self.mode.as_deref() == Some(SYMLINK_MODE)
+    }
+
+    pub fn is_executable(&self) -> bool {
+        self.mode.as_deref() == Some(EXECUTABLE_MODE)
+    }
+}
+
+/// A submodule pinned in the tree (a `commit` tree entry)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Submodule {
+    pub path: String,
+    pub sha: String,
+    /// From `.gitmodules`; `None` when the file has no entry for `path`
+    #[serde(default)]
+    pub url: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,12 +152,109 @@ pub struct ConvertOptions {
     pub remove_license_headers: bool,
     #[serde(default)]
     pub skip_patterns: Vec<String>,
+    /// Extra repository paths to export together with the URL subdirectory
+    #[serde(default)]
+    pub include_paths: Vec<String>,
+    /// Maximum number of files downloaded at the same time
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    #[serde(default)]
+    pub fetch_strategy: FetchStrategy,
+    /// Export submodules on the same host at their pinned commits under the submodule path
+    #[serde(default)]
+    pub recurse_submodules: bool,
+    /// Fetch the real content of Git LFS files below the large-file threshold
+    /// instead of only listing their pointers
+    #[serde(default)]
+    pub resolve_lfs: bool,
+    #[serde(flatten)]
+    pub connection: ConnectionOptions,
+}
+
+/// How file contents are downloaded from GitHub
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FetchStrategy {
+    /// Archive for large file sets, per-file requests otherwise
+    #[default]
+    Auto,
+    /// One raw request per file
+    PerFile,
+    /// Single tarball download filtered locally
+    Archive,
+}
+
+/// Client settings shared by code and issue exports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionOptions {
+    /// GitHub token; when empty the one from persisted settings is used
+    #[serde(default)]
+    pub token: Option<String>,
+    /// GitHub Enterprise Server profile; github.com when absent
+    #[serde(default)]
+    pub host: Option<HostProfile>,
+    /// Sleep until the rate limit resets instead of failing
+    #[serde(default = "default_true")]
+    pub wait_on_rate_limit: bool,
+    /// Attempts per request, including the first one
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_concurrency() -> usize {
+    8
+}
+
+fn default_max_attempts() -> u32 {
+    4
+}
+
+fn default_request_timeout_secs() -> u64 {
+    30
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostProfile {
+    /// Host as it appears in repository URLs, e.g. `github.com` or `git.corp.example`
+    pub web_host: String,
+    /// REST API root, e.g. `https://api.github.com` or `https://git.corp.example/api/v3`
+    pub api_root: String,
+    /// Raw content root; when absent file bodies are read through the Contents API
+    #[serde(default)]
+    pub raw_root: Option<String>,
+}
+
+impl Default for HostProfile {
+    fn default() -> Self {
+        Self {
+            web_host: "github.com".into(),
+            api_root: "https://api.github.com".into(),
+            raw_root: Some("https://raw.githubusercontent.com".into()),
+        }
+    }
+}
+
+/// A forge instance recognised by its host, e.g. a self-hosted GitLab
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgeHost {
+    pub forge: Forge,
+    /// Host as it appears in repository URLs, e.g. `gitlab.corp.example`
+    pub web_host: String,
+    /// REST API root; derived from `web_host` when absent
+    #[serde(default)]
+    pub api_root: Option<String>,
+    /// Token sent to this host only; `user:secret` is sent as Basic credentials
+    /// (Bitbucket app passwords and API tokens)
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Stats {
     pub files_processed: u64,
@@ -50,6 +263,12 @@ pub struct Stats {
     pub total_lines: u64,
     pub token_count: Option<u64>,
     pub total_files: Option<u64>,
+    /// The file list is incomplete: GitHub truncated the tree and the subtree walk hit its limit
+    #[serde(default)]
+    pub tree_truncated: bool,
+    /// Git LFS files listed by pointer instead of being exported
+    #[serde(default)]
+    pub lfs_objects: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,6 +284,8 @@ pub struct IssuesExportOptions {
     pub include_open: bool,
     #[serde(default)]
     pub include_closed: bool,
+    #[serde(flatten)]
+    pub connection: ConnectionOptions,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -112,6 +333,33 @@ pub struct IssuesExportResult {
     pub stats: IssuesStats,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitStatus {
+    #[serde(default)]
+    pub limit: Option<u64>,
+    pub remaining: u64,
+    /// Unix timestamp (seconds) when the budget resets
+    #[serde(default)]
+    pub reset_at: Option<u64>,
+    /// True while the client sleeps until `reset_at`
+    #[serde(default)]
+    pub waiting: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AppSettings {
+    #[serde(default)]
+    pub github_token: Option<String>,
+    #[serde(default)]
+    pub github_host: Option<HostProfile>,
+    /// Non-GitHub hosts; gitlab.com, codeberg.org and bitbucket.org are recognised without an entry
+    #[serde(default)]
+    pub forge_hosts: Vec<ForgeHost>,
+    /// Size limit of each disk cache (downloaded files, API responses) in MB; 0 turns both off
+    #[serde(default)]
+    pub blob_cache_max_mb: Option<u64>,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum DomainError {
     #[error("Invalid repository format: {0}")]
@@ -120,12 +368,36 @@ pub enum DomainError {
     Network(String),
     #[error("Repository not found or inaccessible")]
     NotFound,
+    #[error("Authentication failed: the access token is invalid, expired or lacks access")]
+    Unauthorized,
+    #[error("Ambiguous branch or tag in URL; candidates: {}", .0.join(", "))]
+    AmbiguousRef(Vec<String>),
+    #[error("API rate limit exceeded, resets at {}", format_unix_time(*.reset_at))]
+    RateLimited { reset_at: u64 },
     #[error("No files to process after filtering")]
     NoFiles,
+    #[error("Issues are disabled for this repository")]
+    IssuesDisabled,
     #[error("Operation cancelled")]
     Cancelled,
+    #[error("Git error: {0}")]
+    Git(String),
     #[error("I/O error: {0}")]
     Io(String),
     #[error("Unexpected error: {0}")]
     Unexpected(String),
 }
+
+impl From<std::io::Error> for DomainError {
+    fn from(err: std::io::Error) -> Self {
+        DomainError::Io(err.to_string())
+    }
+}
+
+fn format_unix_time(ts: u64) -> String {
+    i64::try_from(ts)
+        .ok()
+        .and_then(|ts| OffsetDateTime::from_unix_timestamp(ts).ok())
+        .and_then(|dt| dt.format(&Rfc3339).ok())
+        .unwrap_or_else(|| ts.to_string())
+}
@@ -0,0 +1,353 @@
+use std::future::Future;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::models::{DomainError, RefKind, RepoInfo};
+use crate::source::TreeListing;
+
+pub fn looks_like_sha(name: &str) -> bool {
+    (7..=40).contains(&name.len()) && name.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SplitRef {
+    pub name: String,
+    pub kind: RefKind,
+    /// Остаток пути после имени ref; `None`, если путь целиком — имя ref
+    pub path: Option<String>,
+}
+
+/// Запросы к refs и дереву, через которые GitLab, Gitea и Bitbucket разрешают ref из URL.
+pub trait RefLookup: Sync {
+    /// Коммит, на который указывает ветка, тег или (в том числе сокращённый) SHA;
+    /// `None` — такого ref нет.
+    fn ref_target(
+        &self,
+        owner: &str,
+        repo: &str,
+        kind: RefKind,
+        name: &str,
+        cancel: &CancellationToken,
+    ) -> impl Future<Output = Result<Option<String>, DomainError>> + Send;
+
+    /// Ветка по умолчанию; у пустого репозитория её нет — экспортировать нечего.
+    fn default_branch(
+        &self,
+        owner: &str,
+        repo: &str,
+        cancel: &CancellationToken,
+    ) -> impl Future<Output = Result<String, DomainError>> + Send;
+
+    /// Полный список файлов коммита `sha`.
+    fn get_tree(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+        cancel: &CancellationToken,
+    ) -> impl Future<Output = Result<TreeListing, DomainError>> + Send;
+}
+
+/// Определяет ref (по умолчанию — ветку по умолчанию), фиксирует его коммит и читает
+/// дерево именно этого коммита: дальнейшие загрузки идут по SHA, так что push во время
+/// экспорта не смешает состояния.
+pub async fn resolve_repository_files<C: RefLookup>(
+    client: &C,
+    input: &RepoInfo,
+    cancel: &CancellationToken,
+) -> Result<(RepoInfo, TreeListing), DomainError> {
+    let (owner, repo) = (input.owner.as_str(), input.repo.as_str());
+    let mut info = input.clone();
+    let (name, kind, sha) = match (&input.ref_path, &input.branch) {
+        (Some(ref_path), _) => {
+            let (split, sha) =
+                split_ref_path(client, owner, repo, ref_path, input.ref_kind, cancel).await?;
+            info.subdirectory = split.path;
+            (split.name, split.kind, sha)
+        }
+        (None, Some(name)) => {
+            let (kind, sha) =
+                resolve_ref(client, owner, repo, name, input.ref_kind, cancel).await?;
+            (name.clone(), kind, sha)
+        }
+        (None, None) => {
+            let branch = client.default_branch(owner, repo, cancel).await?;
+            let (kind, sha) =
+                resolve_ref(client, owner, repo, &branch, Some(RefKind::Branch), cancel).await?;
+            (branch, kind, sha)
+        }
+    };
+
+    let listing = client.get_tree(owner, repo, &sha, cancel).await?;
+
+    info.branch = Some(name);
+    info.commit_sha = Some(sha);
+    info.ref_kind = Some(kind);
+    Ok((info, listing))
+}
+
+/// Тип и коммит ref. Без известного типа тег важнее ветки с тем же именем (git проверяет
+/// `refs/tags` раньше `refs/heads`), SHA — последний вариант.
+pub async fn resolve_ref<C: RefLookup>(
+    client: &C,
+    owner: &str,
+    repo: &str,
+    name: &str,
+    kind: Option<RefKind>,
+    cancel: &CancellationToken,
+) -> Result<(RefKind, String), DomainError> {
+    let kinds = match kind {
+        Some(kind) => vec![kind],
+        None if looks_like_sha(name) => vec![RefKind::Tag, RefKind::Branch, RefKind::Commit],
+        None => vec![RefKind::Tag, RefKind::Branch],
+    };
+    for kind in kinds {
+        if let Some(sha) = client.ref_target(owner, repo, kind, name, cancel).await? {
+            return Ok((kind, sha));
+        }
+    }
+
+    Err(DomainError::NotFound)
+}
+
+/// Делит `feature/auth/docs` из URL на ref и путь по самому длинному существующему имени
+/// из целых сегментов; `kind` — тип ref, если он записан в самом URL (`src/branch/...`).
+/// Полный хеш в первом сегменте — почти наверняка коммит (так выглядят ссылки
+/// «скопировать»), и перебор префиксов как веток для него не нужен.
+pub async fn split_ref_path<C: RefLookup>(
+    client: &C,
+    owner: &str,
+    repo: &str,
+    ref_path: &str,
+    kind: Option<RefKind>,
+    cancel: &CancellationToken,
+) -> Result<(SplitRef, String), DomainError> {
+    let segments: Vec<&str> = ref_path.split('/').collect();
+    let named_kinds = match kind {
+        Some(RefKind::Commit) => vec![],
+        Some(kind) => vec![kind],
+        None => vec![RefKind::Tag, RefKind::Branch],
+    };
+    let may_be_commit = kind.is_none_or(|kind| kind == RefKind::Commit);
+
+    if may_be_commit
+        && let Some(split) = commit_prefix(client, owner, repo, &segments, 40, cancel).await?
+    {
+        return Ok(split);
+    }
+
+    for len in (1..=segments.len()).rev() {
+        let name = segments[..len].join("/");
+        for kind in named_kinds.iter().copied() {
+            if let Some(sha) = client.ref_target(owner, repo, kind, &name, cancel).await? {
+                let path = Some(segments[len..].join("/")).filter(|p| !p.is_empty());
+                return Ok((SplitRef { name, kind, path }, sha));
+            }
+        }
+    }
+
+    if !may_be_commit {
+        return Err(DomainError::NotFound);
+    }
+    commit_prefix(client, owner, repo, &segments, 7, cancel)
+        .await?
+        .ok_or(DomainError::NotFound)
+}
+
+// Первый сегмент как SHA коммита, если он не короче `min_len` символов и коммит существует
+async fn commit_prefix<C: RefLookup>(
+    client: &C,
+    owner: &str,
+    repo: &str,
+    segments: &[&str],
+    min_len: usize,
+    cancel: &CancellationToken,
+) -> Result<Option<(SplitRef, String)>, DomainError> {
+    let Some((first, rest)) = segments
+        .split_first()
+        .filter(|(first, _)| first.len() >= min_len && looks_like_sha(first))
+    else {
+        return Ok(None);
+    };
+    let sha = client
+        .ref_target(owner, repo, RefKind::Commit, first, cancel)
+        .await?;
+    Ok(sha.map(|sha| {
+        let split = SplitRef {
+            name: first.to_string(),
+            kind: RefKind::Commit,
+            path: Some(rest.join("/")).filter(|p| !p.is_empty()),
+        };
+        (split, sha)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FULL_SHA: &str = "0123456789abcdef0123456789abcdef01234567";
+
+    // Refs репозитория; коммит находится и по сокращённому SHA, как в API хостингов
+    struct FakeRefs(Vec<(RefKind, &'static str, &'static str)>);
+
+    impl FakeRefs {
+        fn new() -> Self {
+            Self(vec![
+                (RefKind::Branch, "main", "aaaaaaa"),
+                (RefKind::Branch, "feature/auth", "bbbbbbb"),
+                (RefKind::Tag, "feature", "ccccccc"),
+                (RefKind::Branch, "v1", "ddddddd"),
+                (RefKind::Tag, "v1", "eeeeeee"),
+                (RefKind::Branch, "0123456", "fffffff"),
+            ])
+        }
+    }
+
+    impl RefLookup for FakeRefs {
+        async fn ref_target(
+            &self,
+            _owner: &str,
+            _repo: &str,
+            kind: RefKind,
+            name: &str,
+            _cancel: &CancellationToken,
+        ) -> Result<Option<String>, DomainError> {
+            if kind == RefKind::Commit {
+                return Ok(FULL_SHA.starts_with(name).then(|| FULL_SHA.to_string()));
+            }
+            Ok(self
+                .0
+                .iter()
+                .find(|(k, n, _)| *k == kind && *n == name)
+                .map(|(_, _, sha)| sha.to_string()))
+        }
+
+        async fn default_branch(
+            &self,
+            _owner: &str,
+            _repo: &str,
+            _cancel: &CancellationToken,
+        ) -> Result<String, DomainError> {
+            Ok("main".into())
+        }
+
+        async fn get_tree(
+            &self,
+            _owner: &str,
+            _repo: &str,
+            _sha: &str,
+            _cancel: &CancellationToken,
+        ) -> Result<TreeListing, DomainError> {
+            Ok(TreeListing {
+                files: Vec::new(),
+                truncated: false,
+                submodules: Vec::new(),
+                unreadable: 0,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn splits_ref_paths_against_existing_refs() {
+        let client = FakeRefs::new();
+        let full_sha_path = format!("{FULL_SHA}/src");
+        let split = |name: &str, kind, path: Option<&str>| {
+            Some(SplitRef {
+                name: name.into(),
+                kind,
+                path: path.map(Into::into),
+            })
+        };
+        let cases = [
+            ("main", None, split("main", RefKind::Branch, None)),
+            (
+                "feature/auth/src/lib.rs",
+                None,
+                split("feature/auth", RefKind::Branch, Some("src/lib.rs")),
+            ),
+            (
+                "feature/docs",
+                None,
+                split("feature", RefKind::Tag, Some("docs")),
+            ),
+            ("v1/docs", None, split("v1", RefKind::Tag, Some("docs"))),
+            (
+                "v1/docs",
+                Some(RefKind::Branch),
+                split("v1", RefKind::Branch, Some("docs")),
+            ),
+            (
+                &full_sha_path,
+                None,
+                split(FULL_SHA, RefKind::Commit, Some("src")),
+            ),
+            (
+                "0123456/src",
+                None,
+                split("0123456", RefKind::Branch, Some("src")),
+            ),
+            (
+                "0123456/src",
+                Some(RefKind::Commit),
+                split("0123456", RefKind::Commit, Some("src")),
+            ),
+            ("main/src", Some(RefKind::Tag), None),
+            ("missing/src", None, None),
+        ];
+
+        for (ref_path, kind, expected) in cases {
+            let result =
+                split_ref_path(&client, "o", "r", ref_path, kind, &CancellationToken::new())
+                    .await
+                    .map(|(split, _)| split);
+            match expected {
+                Some(expected) => assert_eq!(result.unwrap(), expected, "{ref_path}"),
+                None => assert!(matches!(result, Err(DomainError::NotFound)), "{ref_path}"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn resolves_default_branch_and_tags_before_branches() {
+        let client = FakeRefs::new();
+        let cancel = CancellationToken::new();
+        let mut input = crate::local::directory::local_repo_info(std::path::Path::new("."));
+
+        let (info, _) = resolve_repository_files(&client, &input, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(info.branch.as_deref(), Some("main"));
+        assert_eq!(info.commit_sha.as_deref(), Some("aaaaaaa"));
+
+        input.branch = Some("v1".into());
+        let (info, _) = resolve_repository_files(&client, &input, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(info.ref_kind, Some(RefKind::Tag));
+        assert_eq!(info.commit_sha.as_deref(), Some("eeeeeee"));
+
+        input.branch = Some("0123456789".into());
+        let (info, _) = resolve_repository_files(&client, &input, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(info.ref_kind, Some(RefKind::Commit));
+        assert_eq!(info.commit_sha.as_deref(), Some(FULL_SHA));
+    }
+
+    #[test]
+    fn recognises_full_and_abbreviated_shas() {
+        let cases = [
+            ("a1b2c3d", true),
+            ("0123456789abcdef0123456789abcdef01234567", true),
+            ("A1B2C3D4", true),
+            ("a1b2c3", false),
+            ("0123456789abcdef0123456789abcdef012345678", false),
+            ("deadbeeg", false),
+            ("main", false),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(looks_like_sha(name), expected, "{name}");
+        }
+    }
+}
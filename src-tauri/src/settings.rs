@@ -0,0 +1,106 @@
+use std::fs;
+use std::path::Path;
+
+use crate::github::parser::same_host;
+use crate::models::{AppSettings, DomainError};
+
+const SETTINGS_FILE: &str = "settings.json";
+/// Заглушка вместо токена в настройках, отданных интерфейсу: сами токены только
+/// принимаются от него и из бэкенда не уходят.
+pub const TOKEN_MASK: &str = "********";
+
+/// Отсутствующий или повреждённый файл настроек не считается ошибкой —
+/// приложение просто стартует с настройками по умолчанию.
+pub fn load_settings(config_dir: &Path) -> AppSettings {
+    fs::read_to_string(config_dir.join(SETTINGS_FILE))
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_settings(config_dir: &Path, settings: &AppSettings) -> Result<(), DomainError> {
+    fs::create_dir_all(config_dir).map_err(|e| DomainError::Io(e.to_string()))?;
+    let raw = serde_json::to_string_pretty(settings)
+        .map_err(|e| DomainError::Unexpected(e.to_string()))?;
+    fs::write(config_dir.join(SETTINGS_FILE), raw).map_err(|e| DomainError::Io(e.to_string()))
+}
+
+/// Настройки для интерфейса: заданные токены заменены на `TOKEN_MASK`.
+pub fn mask_tokens(mut settings: AppSettings) -> AppSettings {
+    let mask = |token: &mut Option<String>| {
+        if token.is_some() {
+            *token = Some(TOKEN_MASK.to_string());
+        }
+    };
+    mask(&mut settings.github_token);
+    settings
+        .forge_hosts
+        .iter_mut()
+        .for_each(|h| mask(&mut h.token));
+    settings
+}
+
+/// Возвращает на место токенов, пришедших из интерфейса как `TOKEN_MASK`, сохранённые
+/// значения; токен хоста ищется по его `web_host`. Пустой токен удаляет сохранённый.
+pub fn restore_tokens(mut incoming: AppSettings, stored: &AppSettings) -> AppSettings {
+    let restore = |token: &mut Option<String>, saved: Option<&String>| match token
+        .as_deref()
+        .map(str::trim)
+    {
+        Some(TOKEN_MASK) => *token = saved.cloned(),
+        Some("") => *token = None,
+        _ => {}
+    };
+    restore(&mut incoming.github_token, stored.github_token.as_ref());
+    for host in &mut incoming.forge_hosts {
+        let saved = stored
+            .forge_hosts
+            .iter()
+            .find(|h| same_host(&h.web_host, &host.web_host))
+            .and_then(|h| h.token.as_ref());
+        restore(&mut host.token, saved);
+    }
+    incoming
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Forge, ForgeHost};
+
+    fn settings(github_token: Option<&str>, host_token: Option<&str>) -> AppSettings {
+        AppSettings {
+            github_token: github_token.map(str::to_string),
+            forge_hosts: vec![ForgeHost {
+                forge: Forge::GitLab,
+                web_host: "gitlab.corp.example".to_string(),
+                api_root: None,
+                token: host_token.map(str::to_string),
+            }],
+            ..AppSettings::default()
+        }
+    }
+
+    #[test]
+    fn masks_only_present_tokens() {
+        let masked = mask_tokens(settings(Some("ghp_secret"), None));
+        assert_eq!(masked.github_token.as_deref(), Some(TOKEN_MASK));
+        assert_eq!(masked.forge_hosts[0].token, None);
+    }
+
+    #[test]
+    fn masked_tokens_keep_stored_values() {
+        let stored = settings(Some("ghp_secret"), Some("glpat_secret"));
+        let saved = restore_tokens(mask_tokens(stored.clone()), &stored);
+        assert_eq!(saved.github_token.as_deref(), Some("ghp_secret"));
+        assert_eq!(saved.forge_hosts[0].token.as_deref(), Some("glpat_secret"));
+    }
+
+    #[test]
+    fn new_and_empty_tokens_replace_stored_ones() {
+        let stored = settings(Some("ghp_old"), Some("glpat_old"));
+        let saved = restore_tokens(settings(Some("ghp_new"), Some("  ")), &stored);
+        assert_eq!(saved.github_token.as_deref(), Some("ghp_new"));
+        assert_eq!(saved.forge_hosts[0].token, None);
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::io::BufReader;
+
+use futures::stream::BoxStream;
+use futures::{StreamExt, stream};
+use tempfile::NamedTempFile;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::converter::archive::read_tar_gz;
+use crate::lfs::LfsPointer;
+use crate::models::{DomainError, FetchStrategy, FileEntry, Issue, RefKind, RepoInfo, Submodule};
+
+// С этого числа файлов в режиме Auto один архив дешевле, чем запрос на каждый файл
+const ARCHIVE_MIN_FILES: usize = 50;
+// Сколько прочитанных файлов может ждать записи, прежде чем чтение архива приостановится
+const FILE_STREAM_BUFFER: usize = 16;
+
+/// Файлы пакетного чтения по мере распаковки: путь в источнике и содержимое.
+pub type FileStream = BoxStream<'static, Result<(String, String), DomainError>>;
+
+pub struct TreeListing {
+    pub files: Vec<FileEntry>,
+    /// Список неполный даже после обхода поддеревьев
+    pub truncated: bool,
+    /// Подмодули без URL: его знает только `.gitmodules`
+    pub submodules: Vec<Submodule>,
+    /// Записи, которые не удалось прочитать при обходе; в статистике — пропущенные
+    pub unreadable: u64,
+}
+
+/// Откуда берутся файлы экспорта: хостинг (GitHub, GitLab), каталог, локальный клон или архив.
+/// Процессор фильтрует список и пишет содержимое, не зная, где оно лежит.
+pub trait Source: Sync {
+    /// Уточняет ref и коммит для `input` и перечисляет файлы.
+    fn list_files(
+        &self,
+        input: &RepoInfo,
+        cancel: &CancellationToken,
+    ) -> impl Future<Output = Result<(RepoInfo, TreeListing), DomainError>> + Send;
+
+    /// Содержимое одного файла; `None` — файл недоступен и считается пропущенным.
+    fn read_file(
+        &self,
+        repo: &RepoInfo,
+        path: &str,
+        cancel: &CancellationToken,
+    ) -> impl Future<Output = Result<Option<String>, DomainError>> + Send;
+
+    /// Содержимое многих файлов за один проход (архив, объекты git), если при `strategy`
+    /// это выгоднее, — потоком в порядке чтения; файлов, которых в источнике нет, в нём нет.
+    /// `None` — файлы читаются по одному через `read_file`.
+    fn read_files(
+        &self,
+        _repo: &RepoInfo,
+        _paths: &[&str],
+        _strategy: FetchStrategy,
+        _cancel: &CancellationToken,
+    ) -> impl Future<Output = Result<Option<FileStream>, DomainError>> + Send {
+        async { Ok(None) }
+    }
+
+    /// Строки об источнике для раздела Repository Information.
+    fn header_lines(&self, repo: &RepoInfo) -> String {
+        ref_lines(repo)
+    }
+
+    /// Путь, на который указывает симлинк (содержимое его blob).
+    fn read_link_target(
+        &self,
+        repo: &RepoInfo,
+        file: &FileEntry,
+        cancel: &CancellationToken,
+    ) -> impl Future<Output = Result<Option<String>, DomainError>> + Send {
+        self.read_file(repo, &file.path, cancel)
+    }
+
+    /// Настоящее содержимое файла из Git LFS; `None` — объект недоступен или бинарный.
+    fn read_lfs_object(
+        &self,
+        _repo: &RepoInfo,
+        _pointer: &LfsPointer,
+        _cancel: &CancellationToken,
+    ) -> impl Future<Output = Result<Option<String>, DomainError>> + Send {
+        async { Ok(None) }
+    }
+
+    /// Репозиторий подмодуля по URL из `.gitmodules`, если его читает этот же источник.
+    fn submodule_input(&self, _url: &str) -> Option<RepoInfo> {
+        None
+    }
+}
+
+/// Issues удалённого репозитория для экспорта.
+pub trait IssueSource: Sync {
+    /// Issues без pull/merge requests; флаг — список обрезан лимитом страниц.
+    fn fetch_issues(
+        &self,
+        repo: &RepoInfo,
+        cancel: &CancellationToken,
+    ) -> impl Future<Output = Result<(Vec<Issue>, bool), DomainError>> + Send;
+}
+
+/// Строки `Ref`/`Commit` для раздела Repository Information.
+pub fn ref_lines(repo_info: &RepoInfo) -> String {
+    let branch = repo_info.branch.as_deref().unwrap_or("unknown");
+    let ref_kind = repo_info.ref_kind.unwrap_or(RefKind::Branch).as_str();
+    let commit = repo_info.commit_sha.as_deref().unwrap_or("unknown");
+    format!("- **Ref:** {branch} ({ref_kind})\n- **Commit:** {commit}\n")
+}
+
+// Содержимое читаем по зафиксированному SHA, а не по имени ветки
+pub fn content_ref(repo_info: &RepoInfo) -> &str {
+    repo_info
+        .commit_sha
+        .as_deref()
+        .or(repo_info.branch.as_deref())
+        .unwrap_or("main")
+}
+
+pub fn choose_fetch_strategy(strategy: FetchStrategy, file_count: usize) -> FetchStrategy {
+    match strategy {
+        FetchStrategy::Auto if file_count >= ARCHIVE_MIN_FILES => FetchStrategy::Archive,
+        FetchStrategy::Auto => FetchStrategy::PerFile,
+        strategy => strategy,
+    }
+}
+
+/// Выполняет `read` в блокирующей задаче и отдаёт прочитанные файлы потоком: в памяти
+/// одновременно лишь несколько файлов, а закрытый поток останавливает чтение.
+/// `read` передаёт каждый файл в функцию отправки; её ошибка — знак прекратить чтение.
+pub fn stream_files(
+    read: impl FnOnce(
+        &mut dyn FnMut(String, String) -> Result<(), DomainError>,
+    ) -> Result<(), DomainError>
+    + Send
+    + 'static,
+) -> FileStream {
+    let (sender, receiver) = mpsc::channel(FILE_STREAM_BUFFER);
+    tokio::task::spawn_blocking(move || {
+        let result = read(&mut |path, content| {
+            sender
+                .blocking_send(Ok((path, content)))
+                .map_err(|_| DomainError::Cancelled)
+        });
+        if let Err(err) = result {
+            let _ = sender.blocking_send(Err(err));
+        }
+    });
+    stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|item| (item, receiver))
+    })
+    .boxed()
+}
+
+/// Вынимает из скачанного tar.gz-архива хостинга только файлы, прошедшие фильтрацию по дереву.
+/// Архив распаковывается по мере записи экспорта и удаляется, когда поток закрыт.
+pub fn extract_tarball(
+    archive: NamedTempFile,
+    paths: &[&str],
+    cancel: &CancellationToken,
+) -> FileStream {
+    let wanted: HashSet<String> = paths.iter().map(|p| p.to_string()).collect();
+    let cancel = cancel.clone();
+    // Распаковка gzip — CPU-работа, не держим ею async-рантайм
+    stream_files(move |send| {
+        let file = archive
+            .reopen()
+            .map_err(|e| DomainError::Io(e.to_string()))?;
+        read_tar_gz(
+            BufReader::new(file),
+            &cancel,
+            true,
+            |entry| wanted.contains(&entry.path),
+            |file, content| send(file.path, content),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::converter::archive::tests::tar_gz;
+
+    fn repo(branch: Option<&str>, commit_sha: Option<&str>) -> RepoInfo {
+        let mut info = crate::local::directory::local_repo_info(std::path::Path::new("."));
+        info.branch = branch.map(str::to_string);
+        info.commit_sha = commit_sha.map(str::to_string);
+        info
+    }
+
+    #[test]
+    fn content_is_pinned_to_the_resolved_commit() {
+        let sha = "0123456789abcdef0123456789abcdef01234567";
+        assert_eq!(content_ref(&repo(Some("trunk"), Some(sha))), sha);
+        assert_eq!(content_ref(&repo(Some("trunk"), None)), "trunk");
+
+        let mut info = repo(Some("v1.0"), Some(sha));
+        info.ref_kind = Some(RefKind::Tag);
+        assert_eq!(
+            ref_lines(&info),
+            format!("- **Ref:** v1.0 (tag)\n- **Commit:** {sha}\n")
+        );
+    }
+
+    #[test]
+    fn auto_strategy_switches_to_archive_for_large_trees() {
+        let cases = [
+            (FetchStrategy::Auto, 1, FetchStrategy::PerFile),
+            (
+                FetchStrategy::Auto,
+                ARCHIVE_MIN_FILES - 1,
+                FetchStrategy::PerFile,
+            ),
+            (
+                FetchStrategy::Auto,
+                ARCHIVE_MIN_FILES,
+                FetchStrategy::Archive,
+            ),
+            (FetchStrategy::PerFile, 10_000, FetchStrategy::PerFile),
+            (FetchStrategy::Archive, 1, FetchStrategy::Archive),
+        ];
+        for (strategy, count, expected) in cases {
+            assert_eq!(choose_fetch_strategy(strategy, count), expected, "{count}");
+        }
+    }
+
+    #[tokio::test]
+    async fn tarball_yields_only_requested_files() {
+        let mut archive = NamedTempFile::new().unwrap();
+        archive
+            .write_all(&tar_gz(&[
+                ("owner-repo-abc/README.md", "readme\n"),
+                ("owner-repo-abc/src/main.rs", "fn main() {}\n"),
+                ("owner-repo-abc/src/lib.rs", "pub fn lib() {}\n"),
+            ]))
+            .unwrap();
+
+        let files: Vec<(String, String)> = extract_tarball(
+            archive,
+            &["src/main.rs", "README.md"],
+            &CancellationToken::new(),
+        )
+        .map(Result::unwrap)
+        .collect()
+        .await;
+        assert_eq!(
+            files,
+            [
+                ("README.md".to_string(), "readme\n".to_string()),
+                ("src/main.rs".to_string(), "fn main() {}\n".to_string()),
+            ]
+        );
+    }
+}
@@ -5,10 +5,12 @@ import { copyFile, remove } from '@tauri-apps/plugin-fs';
 import { relaunch } from '@tauri-apps/plugin-process';
 import { check as checkUpdate, type DownloadEvent } from '@tauri-apps/plugin-updater';
 import type {
+  AppSettings,
   ConversionResult,
   ConvertOptions,
   IssuesExportOptions,
   IssuesExportResult,
+  RateLimitStatus,
 } from '$lib/types';
 import { encode } from 'gpt-tokenizer';
 
@@ -22,6 +24,12 @@ export function listenConversionProgress(
   });
 }
 
+export function listenRateLimit(cb: (status: RateLimitStatus) => void): Promise<UnlistenFn> {
+  return listen('rate-limit', (event) => {
+    cb(event.payload as RateLimitStatus);
+  });
+}
+
 export async function convertRepo(
   repo: string,
   options: ConvertOptions
@@ -32,6 +40,17 @@ export async function convertRepo(
   });
 }
 
+export async function convertLocal(
+  path: string,
+  options: ConvertOptions,
+  reference?: string
+): Promise<ConversionResult> {
+  return invoke<ConversionResult>('convert_local_to_markdown', {
+    input: { path, reference: reference ?? null },
+    options,
+  });
+}
+
 export async function exportIssues(
   repo: string,
   options: IssuesExportOptions
@@ -42,10 +61,25 @@ export async function exportIssues(
   });
 }
 
+// Saved tokens come back as this placeholder; sending it back keeps the stored token
+export const TOKEN_MASK = '********';
+
+export async function getSettings(): Promise<AppSettings> {
+  return invoke<AppSettings>('get_settings');
+}
+
+export async function saveSettings(settings: AppSettings): Promise<void> {
+  await invoke('save_settings', { settings });
+}
+
 export async function cancelConversion(): Promise<void> {
   await invoke('cancel_conversion');
 }
 
+export async function clearCache(): Promise<number> {
+  return invoke<number>('clear_cache');
+}
+
 export async function downloadFile(
   sourcePath: string,
   suggestedName: string
@@ -6,8 +6,24 @@ export interface RepoInfo {
   branch?: string | null;
   subdirectory?: string | null;
   original_url: string;
+  commit_sha?: string | null;
+  ref_kind?: RefKind | null;
+  ref_path?: string | null;
+  single_file?: boolean;
+  line_range?: LineRange | null;
+  forge?: Forge;
+  host?: string | null;
 }
 
+export type Forge = 'github' | 'gitlab' | 'gitea' | 'bitbucket';
+
+export interface LineRange {
+  start: number;
+  end: number;
+}
+
+export type RefKind = 'branch' | 'tag' | 'commit';
+
 export interface Stats {
   files_processed: number;
   files_skipped: number;
@@ -15,6 +31,8 @@ export interface Stats {
   total_lines: number;
   token_count?: number | null;
   total_files?: number;
+  tree_truncated?: boolean;
+  lfs_objects?: number;
 }
 
 export interface ConversionResult {
@@ -23,17 +41,61 @@ export interface ConversionResult {
   repo: RepoInfo;
 }
 
+export type FetchStrategy = 'auto' | 'per_file' | 'archive';
+
 export interface ConvertOptions {
   include_filenames: boolean;
   add_separators: boolean;
   skip_large_files: boolean;
   remove_license_headers: boolean;
   skip_patterns: string[];
+  include_paths?: string[];
+  concurrency?: number;
+  fetch_strategy?: FetchStrategy;
+  recurse_submodules?: boolean;
+  resolve_lfs?: boolean;
+  token?: string | null;
+  host?: HostProfile | null;
+  wait_on_rate_limit?: boolean;
+  max_attempts?: number;
+  request_timeout_secs?: number;
+}
+
+export interface HostProfile {
+  web_host: string;
+  api_root: string;
+  raw_root?: string | null;
 }
 
 export interface IssuesExportOptions {
   include_open: boolean;
   include_closed: boolean;
+  token?: string | null;
+  host?: HostProfile | null;
+  wait_on_rate_limit?: boolean;
+  max_attempts?: number;
+  request_timeout_secs?: number;
+}
+
+export interface RateLimitStatus {
+  limit?: number | null;
+  remaining: number;
+  reset_at?: number | null;
+  waiting: boolean;
+}
+
+export interface AppSettings {
+  github_token?: string | null;
+  github_host?: HostProfile | null;
+  forge_hosts?: ForgeHost[];
+  blob_cache_max_mb?: number | null;
+}
+
+export interface ForgeHost {
+  forge: Forge;
+  web_host: string;
+  api_root?: string | null;
+  token?: string | null;
 }
 
 export interface IssuesStats {